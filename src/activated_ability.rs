@@ -0,0 +1,411 @@
+use super::{
+    alignment::Alignment, character::Character, item::DamageType, statistics::RunningStatistics,
+    string::align_string,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::max;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbilityTarget {
+    #[default]
+    Any,
+    Evil,
+    Good,
+}
+
+impl AbilityTarget {
+    pub fn matches(&self, alignment: Alignment) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Evil => alignment.is_evil(),
+            Self::Good => alignment.is_good(),
+        }
+    }
+}
+
+// A limited-use ability that is activated during a fight, e.g. a smite.
+// Only fights use them, i.e. `time_to_kill`, `burst`, `duel` and
+// `encounter` of the simulator, not the independent rounds of `begin` and
+// the analyses built on it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActivatedAbility {
+    pub name: String,
+    // Uses per fight.
+    pub uses: i32,
+    // Rounds the ability lasts once activated, `0` if it only applies to
+    // the attack it is used on.
+    pub duration: i32,
+    pub ab: i32,
+    // Damage added to every attack it applies to, multiplied on critical
+    // hits. Of the weapon's base type if there is no damage type.
+    pub dmg: i32,
+    pub dmg_type: Option<DamageType>,
+    // Alignment of the defenders it can be used against.
+    pub target: AbilityTarget,
+    #[serde(default)]
+    pub policy: ActivationPolicy,
+}
+
+impl ActivatedAbility {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            uses: 1,
+            duration: 0,
+            ab: 0,
+            dmg: 0,
+            dmg_type: None,
+            target: AbilityTarget::Any,
+            policy: ActivationPolicy::default(),
+        }
+    }
+
+    // +CHA AB and +level damage on a single attack against an evil
+    // defender, once and again every 5 Paladin levels.
+    pub fn smite_evil(paladin_level: i32, cha_mod: i32) -> Self {
+        Self::new("Smite Evil")
+            .uses(1 + paladin_level / 5)
+            .ab(max(0, cha_mod))
+            .damage(paladin_level, None)
+            .target(AbilityTarget::Evil)
+    }
+
+    // Same as Smite Evil against good defenders, from Blackguard level 2.
+    pub fn smite_good(blackguard_level: i32, cha_mod: i32) -> Self {
+        Self::new("Smite Good")
+            .uses(if blackguard_level >= 2 {
+                1 + blackguard_level / 5
+            } else {
+                0
+            })
+            .ab(max(0, cha_mod))
+            .damage(blackguard_level, None)
+            .target(AbilityTarget::Good)
+    }
+
+    // +CHA divine damage for as many rounds as the CHA modifier.
+    pub fn divine_might(cha_mod: i32) -> Self {
+        Self::new("Divine Might")
+            .duration(max(1, cha_mod))
+            .damage(max(0, cha_mod), Some(DamageType::Divine))
+    }
+
+    pub fn uses(mut self, uses: i32) -> Self {
+        self.uses = uses;
+        self
+    }
+
+    pub fn duration(mut self, duration: i32) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn ab(mut self, ab: i32) -> Self {
+        self.ab = ab;
+        self
+    }
+
+    pub fn damage(mut self, dmg: i32, dmg_type: Option<DamageType>) -> Self {
+        self.dmg = dmg;
+        self.dmg_type = dmg_type;
+        self
+    }
+
+    pub fn target(mut self, target: AbilityTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn policy(mut self, policy: ActivationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn is_single_attack(&self) -> bool {
+        self.duration < 1
+    }
+}
+
+// When the attacker spends the uses of an activated ability in a fight.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActivationPolicy {
+    // The ability stays unused, e.g. for a baseline.
+    Never,
+    // Every use is spent as soon as possible. Single attack abilities are
+    // used on the first attack of each round, the others again as soon as
+    // they expire.
+    #[default]
+    Immediately,
+    // Same as `Immediately`, starting from the given round.
+    FromRound(i32),
+}
+
+impl ActivationPolicy {
+    fn allows(&self, round: i32) -> bool {
+        match self {
+            Self::Never => false,
+            Self::Immediately => true,
+            Self::FromRound(first_round) => round >= *first_round,
+        }
+    }
+}
+
+// Uses left and active durations of an attacker's abilities over a fight.
+// The default state has no abilities.
+#[derive(Default)]
+pub(crate) struct AbilityState {
+    abilities: Vec<ActivatedAbility>,
+    round: i32,
+    remaining_uses: Vec<i32>,
+    // Last round each ability with a duration is active in.
+    active_until: Vec<i32>,
+}
+
+impl AbilityState {
+    // Abilities that are never used or can't be used against the defender
+    // are left out.
+    pub fn new(attacker: &Character, defender: &Character) -> Self {
+        let abilities = attacker
+            .activated_abilities
+            .iter()
+            .filter(|x| x.policy != ActivationPolicy::Never && x.target.matches(defender.alignment))
+            .cloned()
+            .collect::<Vec<ActivatedAbility>>();
+
+        Self {
+            remaining_uses: abilities.iter().map(|x| x.uses).collect(),
+            active_until: vec![0; abilities.len()],
+            abilities,
+            round: 0,
+        }
+    }
+
+    // Starts the next round of the fight, activating abilities with a
+    // duration that aren't active.
+    pub fn next_round(&mut self) {
+        self.round += 1;
+
+        for (i, ability) in self.abilities.iter().enumerate() {
+            if ability.is_single_attack()
+                || !ability.policy.allows(self.round)
+                || self.remaining_uses[i] < 1
+                || self.active_until[i] >= self.round
+            {
+                continue;
+            }
+
+            self.remaining_uses[i] -= 1;
+            self.active_until[i] = self.round + ability.duration - 1;
+        }
+    }
+
+    // Abilities that apply to the attack, using single attack abilities on
+    // the first attack of the round.
+    pub fn attack_abilities(&mut self, atk_no: i32) -> Vec<&ActivatedAbility> {
+        let mut result = vec![];
+
+        for (i, ability) in self.abilities.iter().enumerate() {
+            if !ability.is_single_attack() {
+                if self.active_until[i] >= self.round {
+                    result.push(ability);
+                }
+
+                continue;
+            }
+
+            if atk_no == 1 && self.remaining_uses[i] > 0 && ability.policy.allows(self.round) {
+                self.remaining_uses[i] -= 1;
+                result.push(ability);
+            }
+        }
+
+        result
+    }
+}
+
+// Damage dealt over the first rounds of fights, where activated abilities
+// make the most difference.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct BurstResult {
+    // Damage dealt in each round of the fights, in order.
+    dmg_per_round: Vec<RunningStatistics>,
+    total_dmg: RunningStatistics,
+}
+
+impl BurstResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds a fight with the damage dealt in each of its rounds.
+    pub fn add(&mut self, round_dmg: &[i32]) {
+        while self.dmg_per_round.len() < round_dmg.len() {
+            self.dmg_per_round.push(RunningStatistics::new());
+        }
+
+        for (statistics, dmg) in self.dmg_per_round.iter_mut().zip(round_dmg) {
+            statistics.add(*dmg as f64);
+        }
+
+        self.total_dmg.add(round_dmg.iter().sum::<i32>() as f64);
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.dmg_per_round.len()
+    }
+
+    pub fn dmg_per_round(&self) -> &Vec<RunningStatistics> {
+        &self.dmg_per_round
+    }
+
+    pub fn total_dmg(&self) -> &RunningStatistics {
+        &self.total_dmg
+    }
+}
+
+impl std::fmt::Display for BurstResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string_list = vec![align_string(
+            &format!("BURST DAMAGE ({} ROUNDS)", self.rounds()),
+            format!("{:.2}", self.total_dmg.mean()),
+        )];
+
+        for (i, statistics) in self.dmg_per_round.iter().enumerate() {
+            string_list.push(align_string(
+                &format!("    * ROUND {}", i + 1),
+                format!("{:.2}", statistics.mean()),
+            ));
+        }
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AbilityState, ActivatedAbility, ActivationPolicy};
+    use crate::{
+        alignment::Alignment,
+        character::{AbilityList, Character, CharacterBuilder},
+        combat::{Combat, RoundState},
+        damage_source::DamageSource,
+        item::{weapon_db::get_weapon_base, DamageType, Weapon},
+        simulator::CombatSimulator,
+    };
+
+    #[test]
+    fn activated_abilities() {
+        let smite = ActivatedAbility::smite_evil(10, 4);
+        assert_eq!(smite.uses, 3);
+        assert_eq!(smite.ab, 4);
+        assert_eq!(smite.dmg, 10);
+        assert_eq!(ActivatedAbility::smite_good(1, 4).uses, 0);
+
+        let divine_might = ActivatedAbility::divine_might(3);
+        assert_eq!(divine_might.duration, 3);
+        assert_eq!(divine_might.dmg_type, Some(DamageType::Divine));
+
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(100)
+            .base_apr(2)
+            .add_activated_ability(ActivatedAbility::smite_evil(1, 4))
+            .add_activated_ability(ActivatedAbility::divine_might(2).uses(2))
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let evil = Character::builder()
+            .alignment(Alignment::ChaoticEvil)
+            .ac(10)
+            .hit_points(1000)
+            .build();
+        let neutral = Character::builder().ac(10).build();
+
+        let mut state = AbilityState::new(&attacker, &evil);
+        let mut active = vec![];
+
+        for _ in 0..5 {
+            state.next_round();
+            active.push(
+                (1..=2)
+                    .map(|atk_no| state.attack_abilities(atk_no).len())
+                    .collect::<Vec<usize>>(),
+            );
+        }
+
+        // Smite on the first attack of round 1, Divine Might for rounds 1-4.
+        assert_eq!(
+            active,
+            vec![vec![2, 1], vec![1, 1], vec![1, 1], vec![1, 1], vec![0, 0]]
+        );
+
+        let delayed = CharacterBuilder::from(attacker.clone())
+            .activated_abilities(vec![
+                ActivatedAbility::smite_evil(1, 4).policy(ActivationPolicy::Never),
+                ActivatedAbility::divine_might(2).policy(ActivationPolicy::FromRound(2)),
+            ])
+            .build();
+        let mut state = AbilityState::new(&delayed, &evil);
+        state.next_round();
+        assert!(state.attack_abilities(1).is_empty());
+        state.next_round();
+        assert_eq!(state.attack_abilities(1)[0].name, "Divine Might");
+        assert_eq!(state.attack_abilities(2).len(), 1);
+
+        // Abilities that can't target the defender are left out.
+        let mut state = AbilityState::new(&attacker, &neutral);
+        state.next_round();
+        assert_eq!(state.attack_abilities(1)[0].name, "Divine Might");
+        assert_eq!(state.attack_abilities(1).len(), 1);
+
+        // The single smite lands on the first attack of round 1 only, with
+        // its damage doubled on critical hits.
+        let smite_dmg_per_hit = attacker.activated_abilities[0].dmg as i64;
+        let smite_source = DamageSource::Ability("Smite Evil".into());
+        let combat = Combat::new(&attacker, &evil);
+
+        for _ in 0..20 {
+            let mut state = AbilityState::new(&attacker, &evil);
+
+            for round in 1..=3 {
+                let statistics = combat.resolve_fight_round(&mut RoundState::default(), &mut state);
+                let smite_dmg = statistics
+                    .dmg_source_statistics(smite_source.clone())
+                    .map_or(0, |x| x.amount);
+
+                if round == 1 {
+                    let first_attack = &statistics.attack_slots[0];
+                    assert_eq!(
+                        smite_dmg,
+                        smite_dmg_per_hit * (first_attack.total_hits + first_attack.critical_hits)
+                    );
+                } else {
+                    assert_eq!(smite_dmg, 0);
+                }
+            }
+        }
+
+        let simulator = CombatSimulator::new(60);
+        let result = simulator.burst(&attacker, &evil, 3);
+        assert_eq!(result.rounds(), 3);
+        assert_eq!(result.total_dmg().count(), 20);
+        assert!(result.to_string().contains("ROUND 3"));
+
+        let baseline = CharacterBuilder::from(attacker.clone())
+            .activated_abilities(vec![])
+            .build();
+        assert!(
+            simulator.burst(&baseline, &evil, 3).total_dmg().mean() < result.total_dmg().mean()
+        );
+
+        // Encounters are fights too, independent rounds aren't.
+        let divine_might = DamageSource::Ability("Divine Might".into());
+        let encounter = simulator.encounter(std::slice::from_ref(&attacker), &evil, 1);
+        assert!(encounter
+            .attacker(0)
+            .unwrap()
+            .dmg_source_statistics(divine_might.clone())
+            .is_some());
+        let statistics = simulator.begin(&attacker, &evil);
+        assert!(statistics.dmg_source_statistics(divine_might).is_none());
+    }
+}
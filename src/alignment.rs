@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Alignment {
+    LawfulGood,
+    NeutralGood,
+    ChaoticGood,
+    LawfulNeutral,
+    #[default]
+    TrueNeutral,
+    ChaoticNeutral,
+    LawfulEvil,
+    NeutralEvil,
+    ChaoticEvil,
+}
+
+impl Alignment {
+    pub fn is_good(&self) -> bool {
+        matches!(
+            self,
+            Self::LawfulGood | Self::NeutralGood | Self::ChaoticGood
+        )
+    }
+
+    pub fn is_evil(&self) -> bool {
+        matches!(
+            self,
+            Self::LawfulEvil | Self::NeutralEvil | Self::ChaoticEvil
+        )
+    }
+
+    pub fn is_lawful(&self) -> bool {
+        matches!(
+            self,
+            Self::LawfulGood | Self::LawfulNeutral | Self::LawfulEvil
+        )
+    }
+
+    pub fn is_chaotic(&self) -> bool {
+        matches!(
+            self,
+            Self::ChaoticGood | Self::ChaoticNeutral | Self::ChaoticEvil
+        )
+    }
+}
+
+impl std::fmt::Display for Alignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::LawfulGood => "Lawful Good",
+            Self::NeutralGood => "Neutral Good",
+            Self::ChaoticGood => "Chaotic Good",
+            Self::LawfulNeutral => "Lawful Neutral",
+            Self::TrueNeutral => "True Neutral",
+            Self::ChaoticNeutral => "Chaotic Neutral",
+            Self::LawfulEvil => "Lawful Evil",
+            Self::NeutralEvil => "Neutral Evil",
+            Self::ChaoticEvil => "Chaotic Evil",
+        };
+
+        write!(f, "{}", name)
+    }
+}
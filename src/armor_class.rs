@@ -0,0 +1,285 @@
+use super::{character::AbilityList, combat::AttackContext, string::align_string};
+use serde::{Deserialize, Serialize};
+use std::cmp::{max, min};
+
+pub const BASE_AC: i32 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AcType {
+    Armor,
+    Shield,
+    Natural,
+    Deflection,
+    // The only type that stacks with itself.
+    Dodge,
+}
+
+impl std::fmt::Display for AcType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Armor => write!(f, "ARMOR"),
+            Self::Shield => write!(f, "SHIELD"),
+            Self::Natural => write!(f, "NATURAL"),
+            Self::Deflection => write!(f, "DEFLECTION"),
+            Self::Dodge => write!(f, "DODGE"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AcModifier {
+    pub type_: AcType,
+    pub value: i32,
+}
+
+impl AcModifier {
+    pub fn new(type_: AcType, value: i32) -> Self {
+        Self { type_, value }
+    }
+}
+
+// AC of a character split into typed modifiers, so effects that remove only
+// some of them can be modelled.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArmorClass {
+    // Maximum DEX bonus the worn armor allows, `None` if no armor is worn.
+    pub armor_max_dex: Option<i32>,
+    pub modifiers: Vec<AcModifier>,
+    // Untyped AC that applies in every context, what overriding the AC of a
+    // character with typed components adds.
+    #[serde(default)]
+    pub offset: i32,
+}
+
+impl ArmorClass {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn armor(mut self, value: i32, max_dex: i32) -> Self {
+        self.armor_max_dex = Some(max_dex);
+        self.modifier(AcType::Armor, value)
+    }
+
+    pub fn modifier(mut self, type_: AcType, value: i32) -> Self {
+        self.modifiers.push(AcModifier::new(type_, value));
+        self
+    }
+
+    // Modifiers of the same type don't stack except dodge, only the highest
+    // one applies.
+    pub fn get(&self, type_: AcType) -> i32 {
+        let values = self
+            .modifiers
+            .iter()
+            .filter(|x| x.type_ == type_)
+            .map(|x| x.value);
+
+        if type_ == AcType::Dodge {
+            values.sum()
+        } else {
+            values.max().unwrap_or(0)
+        }
+    }
+
+    pub fn dex_bonus(&self, abilities: &AbilityList) -> i32 {
+        let dex_mod = abilities.dex.get_mod();
+
+        match self.armor_max_dex {
+            Some(max_dex) => min(dex_mod, max_dex),
+            None => dex_mod,
+        }
+    }
+
+    // Monks add their WIS modifier while wearing neither armor nor shield.
+    pub fn wis_bonus(&self, abilities: &AbilityList, is_monk: bool) -> i32 {
+        if !is_monk || self.armor_max_dex.is_some() || self.get(AcType::Shield) > 0 {
+            return 0;
+        }
+
+        max(0, abilities.wis.get_mod())
+    }
+
+    // AC against an attack made in the given context. Knockdown is handled
+    // by `Character::effective_ac` as it applies to any AC.
+    pub fn effective(
+        &self,
+        abilities: &AbilityList,
+        is_monk: bool,
+        context: &AttackContext,
+    ) -> i32 {
+        let mut ac = BASE_AC
+            + self.offset
+            + self.get(AcType::Deflection)
+            + self.wis_bonus(abilities, is_monk);
+        let dex_bonus = self.dex_bonus(abilities);
+
+        if !context.touch {
+            ac += self.get(AcType::Armor) + self.get(AcType::Shield) + self.get(AcType::Natural);
+        }
+
+        // A penalty from low DEX still applies when denied DEX.
+        if context.is_denied_dex() {
+            ac += min(0, dex_bonus);
+        } else {
+            ac += dex_bonus + self.get(AcType::Dodge);
+        }
+
+        ac
+    }
+
+    pub fn breakdown_string(&self, abilities: &AbilityList, is_monk: bool) -> String {
+        let mut string_list = vec![
+            align_string(
+                "ARMOR CLASS",
+                self.effective(abilities, is_monk, &AttackContext::default())
+                    .to_string(),
+            ),
+            align_string("    * BASE", BASE_AC.to_string()),
+        ];
+
+        for type_ in [
+            AcType::Armor,
+            AcType::Shield,
+            AcType::Natural,
+            AcType::Deflection,
+            AcType::Dodge,
+        ] {
+            string_list.push(align_string(
+                &format!("    * {}", type_),
+                format!("{:+}", self.get(type_)),
+            ));
+        }
+
+        string_list.push(align_string(
+            "    * DEX",
+            format!("{:+}", self.dex_bonus(abilities)),
+        ));
+
+        if self.wis_bonus(abilities, is_monk) > 0 {
+            string_list.push(align_string(
+                "    * WIS",
+                format!("{:+}", self.wis_bonus(abilities, is_monk)),
+            ));
+        }
+
+        if self.offset != 0 {
+            string_list.push(align_string("    * OTHER", format!("{:+}", self.offset)));
+        }
+
+        string_list.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AcType, ArmorClass};
+    use crate::{
+        character::{AbilityList, Character},
+        combat::AttackContext,
+        feat::feat_db::get_feat,
+        item::{weapon_db::get_weapon_base, Weapon},
+        simulator::CombatSimulator,
+    };
+
+    #[test]
+    fn armor_class() {
+        let abilities = AbilityList::builder().dex(20).wis(18).build();
+        let armor_class = ArmorClass::new()
+            .armor(8, 1)
+            .modifier(AcType::Armor, 5)
+            .modifier(AcType::Shield, 3)
+            .modifier(AcType::Natural, 4)
+            .modifier(AcType::Deflection, 2)
+            .modifier(AcType::Deflection, 5)
+            .modifier(AcType::Dodge, 1)
+            .modifier(AcType::Dodge, 2);
+
+        assert_eq!(armor_class.get(AcType::Armor), 8);
+        assert_eq!(armor_class.get(AcType::Deflection), 5);
+        assert_eq!(armor_class.get(AcType::Dodge), 3);
+        assert_eq!(armor_class.dex_bonus(&abilities), 1);
+        assert_eq!(armor_class.wis_bonus(&abilities, true), 0);
+
+        let context = AttackContext::default();
+        assert_eq!(
+            armor_class.effective(&abilities, false, &context),
+            10 + 8 + 3 + 4 + 5 + 3 + 1
+        );
+
+        let context = AttackContext {
+            flat_footed: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            armor_class.effective(&abilities, false, &context),
+            10 + 8 + 3 + 4 + 5
+        );
+
+        let context = AttackContext {
+            touch: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            armor_class.effective(&abilities, false, &context),
+            10 + 5 + 3 + 1
+        );
+
+        let monk = Character::builder()
+            .abilities(abilities)
+            .feats(vec![get_feat("Monk")])
+            .armor_class(ArmorClass::new().modifier(AcType::Dodge, 1))
+            .build();
+        assert_eq!(monk.effective_ac(&AttackContext::default()), 10 + 1 + 5 + 4);
+
+        let context = AttackContext {
+            flanked: true,
+            knocked_down: true,
+            ..Default::default()
+        };
+        assert_eq!(monk.effective_ac(&context), 10 + 4 - 4);
+        assert!(monk
+            .armor_class
+            .as_ref()
+            .unwrap()
+            .breakdown_string(&monk.abilities, true)
+            .contains("WIS"));
+
+        // Overriding the AC keeps the components, touch attacks still
+        // ignore the armor.
+        let character = Character::builder()
+            .armor_class(ArmorClass::new().armor(8, 1))
+            .ac(30)
+            .build();
+        assert_eq!(character.effective_ac(&AttackContext::default()), 30);
+        assert_eq!(
+            character.effective_ac(&AttackContext {
+                touch: true,
+                ..Default::default()
+            }),
+            30 - 8
+        );
+        assert_eq!(
+            Character::builder().ac(30).build().effective_ac(&context),
+            26
+        );
+
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(20)
+            .base_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let defender = Character::builder()
+            .armor_class(ArmorClass::new().modifier(AcType::Dodge, 30))
+            .build();
+        let simulator = CombatSimulator::new(100);
+        assert!(simulator.begin(&attacker, &defender).total_hits < 50);
+
+        simulator.set_attack_context(AttackContext {
+            flat_footed: true,
+            ..Default::default()
+        });
+        assert!(simulator.begin(&attacker, &defender).total_hits >= 50);
+    }
+}
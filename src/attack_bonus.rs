@@ -0,0 +1,178 @@
+use super::{
+    character::Character, feat::feat_db::get_feat, rules::MAX_EFFECT_AB, string::align_string,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::min;
+
+// AB of a character split into the components it is calculated from.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AttackBonusBreakdown {
+    pub base_attack_bonus: i32,
+    // Ability the attack roll is modified by, e.g. "STR".
+    pub ability: String,
+    pub ability_mod: i32,
+    // AB granted by feats, e.g. Weapon Focus.
+    pub feats: i32,
+    pub size: i32,
+    // Enchantment or attack bonus of the weapon.
+    pub weapon: i32,
+    pub buffs: Vec<(String, i32)>,
+}
+
+impl AttackBonusBreakdown {
+    // BAB comes from the character's class levels, so a character without
+    // class levels has none.
+    pub fn new(character: &Character, buffs: Vec<(String, i32)>) -> Self {
+        let abilities = &character.abilities;
+        let weapon_base = &character.weapon.base;
+        let mut ability = ("STR", abilities.str.get_mod());
+
+        if weapon_base.is_ranged() {
+            ability = ("DEX", abilities.dex.get_mod());
+
+            if character.has_feat(get_feat("Zen Archery")) && abilities.wis.get_mod() > ability.1 {
+                ability = ("WIS", abilities.wis.get_mod());
+            }
+        } else if character.has_feat(get_feat("Weapon Finesse"))
+            && (weapon_base.size < character.size || weapon_base.name == "Rapier")
+            && abilities.dex.get_mod() > ability.1
+        {
+            ability = ("DEX", abilities.dex.get_mod());
+        }
+
+        let feats = character
+            .feat_effects()
+            .iter()
+            .map(|x| x.effect.attack_bonus(character))
+            .sum();
+
+        Self {
+            base_attack_bonus: character.class_levels.base_attack_bonus(),
+            ability: ability.0.into(),
+            ability_mod: ability.1,
+            feats,
+            size: character.size.ab_modifier(),
+            weapon: character.weapon.attack_bonus(),
+            buffs,
+        }
+    }
+
+    fn uncapped_effect_bonus(&self) -> i32 {
+        self.weapon + self.buffs.iter().map(|x| x.1).sum::<i32>()
+    }
+
+    // Weapon and buff bonuses after the cap.
+    pub fn effect_bonus(&self) -> i32 {
+        min(MAX_EFFECT_AB, self.uncapped_effect_bonus())
+    }
+
+    pub fn lost_to_cap(&self) -> i32 {
+        self.uncapped_effect_bonus() - self.effect_bonus()
+    }
+
+    pub fn total(&self) -> i32 {
+        self.base_attack_bonus + self.ability_mod + self.feats + self.size + self.effect_bonus()
+    }
+}
+
+impl std::fmt::Display for AttackBonusBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string_list = vec![
+            align_string("ATTACK BONUS", self.total().to_string()),
+            align_string("    * BAB", self.base_attack_bonus.to_string()),
+            align_string(
+                &format!("    * {}", self.ability),
+                format!("{:+}", self.ability_mod),
+            ),
+            align_string("    * FEATS", format!("{:+}", self.feats)),
+            align_string("    * SIZE", format!("{:+}", self.size)),
+            align_string("    * WEAPON", format!("{:+}", self.weapon)),
+        ];
+
+        for (name, value) in &self.buffs {
+            string_list.push(align_string(
+                &format!("    * {}", name),
+                format!("{:+}", value),
+            ));
+        }
+
+        if self.lost_to_cap() > 0 {
+            string_list.push(align_string(
+                "    * LOST TO CAP",
+                format!("{:+}", -self.lost_to_cap()),
+            ));
+        }
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AttackBonusBreakdown;
+    use crate::{
+        character::{AbilityList, Character},
+        class::{Class, ClassLevels},
+        feat::feat_db::get_feat,
+        item::{weapon_db::get_weapon_base, ItemProperty, Weapon},
+        size::SizeCategory,
+    };
+
+    #[test]
+    fn attack_bonus() {
+        let character = Character::builder()
+            .size(SizeCategory::Small)
+            .abilities(AbilityList::builder().str(14).dex(20).wis(24).build())
+            .feats(vec![
+                get_feat("Weapon Finesse"),
+                get_feat("Weapon Focus"),
+                get_feat("Epic Weapon Focus"),
+            ])
+            .class_levels(ClassLevels(vec![(Class::Fighter, 20)]))
+            .weapon(Weapon::new(
+                "".into(),
+                get_weapon_base("Dagger"),
+                vec![
+                    ItemProperty::EnchantmentBonus(5),
+                    ItemProperty::AttackBonus(7),
+                ],
+            ))
+            .ab_from_components(vec![("Bless".into(), 1), ("Divine Power".into(), 15)])
+            .build();
+
+        let breakdown = AttackBonusBreakdown::new(&character, vec![]);
+        assert_eq!(breakdown.base_attack_bonus, 20);
+        assert_eq!(breakdown.ability, "DEX");
+        assert_eq!(breakdown.ability_mod, 5);
+        assert_eq!(breakdown.feats, 3);
+        assert_eq!(breakdown.size, 1);
+        assert_eq!(breakdown.weapon, 7);
+        assert_eq!(breakdown.total(), 36);
+
+        // 7 + 1 + 15 from the weapon and buffs is capped to 20.
+        assert_eq!(character.ab, 49);
+
+        let breakdown = AttackBonusBreakdown::new(&character, vec![("Divine Power".into(), 15)]);
+        assert_eq!(breakdown.lost_to_cap(), 2);
+        assert!(breakdown.to_string().contains("LOST TO CAP"));
+
+        // Longsword isn't light for a small creature, STR is used.
+        let character = Character::builder()
+            .size(SizeCategory::Small)
+            .abilities(AbilityList::builder().str(14).dex(20).wis(24).build())
+            .feats(vec![get_feat("Weapon Finesse"), get_feat("Zen Archery")])
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let breakdown = AttackBonusBreakdown::new(&character, vec![]);
+        assert_eq!(breakdown.ability, "STR");
+        assert_eq!(breakdown.ability_mod, 2);
+
+        let character = Character {
+            weapon: Weapon::new("".into(), get_weapon_base("Longbow"), vec![]),
+            ..character
+        };
+        let breakdown = AttackBonusBreakdown::new(&character, vec![]);
+        assert_eq!(breakdown.ability, "WIS");
+        assert_eq!(breakdown.ability_mod, 7);
+    }
+}
@@ -3,7 +3,8 @@ use arelith::{
     dice::Dice,
     feat::feat_db::get_feat,
     item::{weapon_db::get_weapon_base, Damage, DamageType, ItemProperty, Weapon},
-    simulator::CombatSimulator,
+    simulator::{CombatSimulator, PrecisionTarget},
+    validation::validate,
 };
 
 fn main() {
@@ -54,7 +55,16 @@ fn main() {
         ))
         .build();
 
-    let simulator = CombatSimulator::new(10);
+    // The example build skips some feat prerequisites, show what is missing
+    // without stopping the simulation.
+    let validation = validate(&attacker);
+    if !validation.issues().is_empty() {
+        println!("{}\n", validation);
+    }
+
+    let simulator = CombatSimulator::new(100_000);
+    simulator.set_precision_target(PrecisionTarget::relative(0.01));
+
     let result = simulator.damage_test(
         &attacker,
         vec![35, 40, 45, 50, 55, 60, 65],
@@ -63,5 +73,5 @@ fn main() {
         5,
         false,
     );
-    println!("{}", result.to_string());
+    println!("{}", result);
 }
@@ -0,0 +1,262 @@
+use super::{combat::Combat, string::align_string};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BreakpointKind {
+    // Threats of the attack no longer confirm on every roll.
+    CritConfirmationFails {
+        atk_no: i32,
+    },
+    // The attack only hits on a natural 20.
+    OnlyNatural20 {
+        atk_no: i32,
+    },
+    // The build and the competing build swap places in DPR.
+    Crossover {
+        competitor_ahead: bool,
+        competitor_before: f64,
+        competitor_after: f64,
+    },
+}
+
+impl std::fmt::Display for BreakpointKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CritConfirmationFails { atk_no } => {
+                write!(f, "ATTACK #{} CONFIRM FAILS", atk_no)
+            }
+            Self::OnlyNatural20 { atk_no } => write!(f, "ATTACK #{} HITS ONLY ON 20", atk_no),
+            Self::Crossover {
+                competitor_ahead, ..
+            } => {
+                if *competitor_ahead {
+                    write!(f, "COMPETITOR OVERTAKES")
+                } else {
+                    write!(f, "OVERTAKES COMPETITOR")
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub kind: BreakpointKind,
+    // First AC the behaviour applies to.
+    pub ac: i32,
+    // DPR of the build against one AC lower and against `ac`.
+    pub dmg_per_round_before: f64,
+    pub dmg_per_round_after: f64,
+}
+
+// ACs at which an attack of the attacker changes behaviour against the
+// defender, following the hit and critical confirmation rolls in
+// `Combat::resolve_attack`. Activated abilities aren't used, as the DPR is
+// taken from independent rounds. Attacks sharing the same AB are reported
+// once, by the first of them.
+pub(crate) fn attack_breakpoints(combat: &Combat) -> Vec<(BreakpointKind, i32)> {
+    let attacker = combat.attacker();
+    let mut seen_ab = vec![];
+    let mut result = vec![];
+
+    for atk_no in 1..=attacker.total_apr() {
+        let atk_info = match attacker.atk_ab(atk_no) {
+            Some(atk_info) => atk_info,
+            None => continue,
+        };
+        let ab = combat.attack_ab(&atk_info, &[]);
+
+        if seen_ab.contains(&ab) {
+            continue;
+        }
+
+        seen_ab.push(ab);
+
+        // Confirmation has no automatic success on a natural 20.
+        result.push((BreakpointKind::CritConfirmationFails { atk_no }, ab + 2));
+        result.push((BreakpointKind::OnlyNatural20 { atk_no }, ab + 20));
+    }
+
+    result
+}
+
+// Compares the DPR of the build and the competitor at consecutive ACs and
+// returns the indices of the entries where the other one takes the lead.
+// Ties keep the previous leader.
+pub(crate) fn crossovers(dmg_per_round: &[(i32, f64, f64)]) -> Vec<usize> {
+    let mut result = vec![];
+    let mut leader = None;
+
+    for (i, (_, dmg, competitor_dmg)) in dmg_per_round.iter().enumerate() {
+        if dmg == competitor_dmg {
+            continue;
+        }
+
+        let competitor_ahead = competitor_dmg > dmg;
+
+        if leader.is_some_and(|x| x != competitor_ahead) {
+            result.push(i);
+        }
+
+        leader = Some(competitor_ahead);
+    }
+
+    result
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BreakpointResult {
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl BreakpointResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+        self.breakpoints.sort_by_key(|x| x.ac);
+    }
+
+    // Ordered by AC.
+    pub fn breakpoints(&self) -> &Vec<Breakpoint> {
+        &self.breakpoints
+    }
+}
+
+impl std::fmt::Display for BreakpointResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string_list = self
+            .breakpoints
+            .iter()
+            .map(|x| {
+                let competitor = match x.kind {
+                    BreakpointKind::Crossover {
+                        competitor_before,
+                        competitor_after,
+                        ..
+                    } => format!(" vs {:.2} -> {:.2}", competitor_before, competitor_after),
+                    _ => "".into(),
+                };
+
+                align_string(
+                    &format!("AC {} {}", x.ac, x.kind),
+                    format!(
+                        "{:.2} -> {:.2} DPR{}",
+                        x.dmg_per_round_before, x.dmg_per_round_after, competitor
+                    ),
+                )
+            })
+            .collect::<Vec<String>>();
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{attack_breakpoints, crossovers, BreakpointKind};
+    use crate::{
+        character::{AbilityList, Character},
+        class::{Class, ClassLevels},
+        combat::{AttackContext, Combat},
+        feat::feat_db::get_feat,
+        item::{weapon_db::get_weapon_base, Weapon},
+        race::RacialType,
+        simulator::CombatSimulator,
+        target::TargetProfile,
+    };
+
+    #[test]
+    fn breakpoints() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(30)
+            .base_apr(4)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+
+        let defender = TargetProfile::default().to_character();
+        let breakpoints = attack_breakpoints(&Combat::new(&attacker, &defender));
+        assert_eq!(breakpoints.len(), 8);
+        assert_eq!(
+            breakpoints[0],
+            (BreakpointKind::CritConfirmationFails { atk_no: 1 }, 32)
+        );
+        assert_eq!(
+            breakpoints[7],
+            (BreakpointKind::OnlyNatural20 { atk_no: 4 }, 35)
+        );
+
+        // AB against the defender counts, as it does when rolling the attack.
+        let ranger = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .class_levels(ClassLevels(vec![(Class::Ranger, 21)]))
+            .feats(vec![get_feat("Bane of Enemies")])
+            .favored_enemies(vec![RacialType::Orc])
+            .ab(30)
+            .base_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let orc = Character::builder().race(RacialType::Orc).build();
+        assert_eq!(
+            attack_breakpoints(&Combat::new(&ranger, &orc))[1],
+            (BreakpointKind::OnlyNatural20 { atk_no: 1 }, 30 + 2 + 20)
+        );
+
+        let table = [
+            (10, 5.0, 3.0),
+            (11, 4.0, 4.0),
+            (12, 3.0, 5.0),
+            (13, 4.0, 3.0),
+        ];
+        assert_eq!(crossovers(&table), vec![2, 3]);
+        assert_eq!(
+            BreakpointKind::Crossover {
+                competitor_ahead: true,
+                competitor_before: 4.0,
+                competitor_after: 5.0,
+            }
+            .to_string(),
+            "COMPETITOR OVERTAKES"
+        );
+
+        let competitor = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(45)
+            .base_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let simulator = CombatSimulator::new(100);
+
+        let result = simulator.breakpoints(
+            &attacker,
+            &TargetProfile::default().to_character(),
+            30..=60,
+            Some(&competitor),
+        );
+
+        assert!(result.breakpoints().windows(2).all(|x| x[0].ac <= x[1].ac));
+        assert!(result
+            .breakpoints()
+            .iter()
+            .any(|x| x.kind == BreakpointKind::OnlyNatural20 { atk_no: 1 } && x.ac == 50));
+
+        // Knocked down defenders are hit as if their AC was 4 lower.
+        simulator.set_attack_context(AttackContext {
+            knocked_down: true,
+            ..Default::default()
+        });
+        let result = simulator.breakpoints(
+            &attacker,
+            &TargetProfile::default().to_character(),
+            30..=60,
+            None,
+        );
+        assert!(result
+            .breakpoints()
+            .iter()
+            .any(|x| x.kind == BreakpointKind::OnlyNatural20 { atk_no: 1 } && x.ac == 54));
+    }
+}
@@ -1,16 +1,32 @@
 use super::{
-    combat::{AttackInfo, AttackType},
-    feat::{feat_db::get_feat, Feat},
-    item::{get_keen_increase, DamageType, Weapon},
-    rules::{CONSECUTIVE_ATTACK_AB_PENALTY, MONK_CONSECUTIVE_ATTACK_AB_PENALTY},
+    activated_ability::ActivatedAbility,
+    alignment::Alignment,
+    armor_class::ArmorClass,
+    attack_bonus::AttackBonusBreakdown,
+    class::{Class, ClassLevels},
+    combat::{AttackContext, AttackInfo, AttackType},
+    feat::{
+        feat_db::{get_feat, get_feat_definition},
+        Feat, FeatDefinition,
+    },
+    item::{DamageType, Weapon, WeaponBase},
+    race::RacialType,
+    rules::{
+        CONSECUTIVE_ATTACK_AB_PENALTY, KNOCKDOWN_AC_PENALTY, MONK_CONSECUTIVE_ATTACK_AB_PENALTY,
+    },
     size::SizeCategory,
 };
 use serde::{Deserialize, Serialize};
+use std::{cmp::max, sync::Arc};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct AbilityScore(i32);
 
 impl AbilityScore {
+    pub fn value(&self) -> i32 {
+        self.0
+    }
+
     pub fn get_mod(&self) -> i32 {
         let score = self.0 - if self.0 < 10 { 1 } else { 0 };
 
@@ -18,19 +34,36 @@ impl AbilityScore {
     }
 }
 
-impl Default for AbilityScore {
-    fn default() -> Self {
-        AbilityScore(0)
-    }
-}
-
 impl From<i32> for AbilityScore {
     fn from(value: i32) -> Self {
         AbilityScore(value)
     }
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Ability {
+    Str,
+    Dex,
+    Con,
+    Int,
+    Wis,
+    Cha,
+}
+
+impl std::fmt::Display for Ability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Str => write!(f, "STR"),
+            Self::Dex => write!(f, "DEX"),
+            Self::Con => write!(f, "CON"),
+            Self::Int => write!(f, "INT"),
+            Self::Wis => write!(f, "WIS"),
+            Self::Cha => write!(f, "CHA"),
+        }
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct AbilityList {
     pub str: AbilityScore,
     pub dex: AbilityScore,
@@ -44,8 +77,20 @@ impl AbilityList {
     pub fn builder() -> AbilityListBuilder {
         AbilityListBuilder::new()
     }
+
+    pub fn get(&self, ability: Ability) -> &AbilityScore {
+        match ability {
+            Ability::Str => &self.str,
+            Ability::Dex => &self.dex,
+            Ability::Con => &self.con,
+            Ability::Int => &self.int,
+            Ability::Wis => &self.wis,
+            Ability::Cha => &self.cha,
+        }
+    }
 }
 
+#[derive(Default)]
 pub struct AbilityListBuilder {
     abilities: AbilityList,
 }
@@ -93,12 +138,17 @@ impl AbilityListBuilder {
     }
 }
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Character {
     pub name: String,
     pub size: SizeCategory,
+    #[serde(default)]
+    pub race: RacialType,
+    #[serde(default)]
+    pub alignment: Alignment,
     pub abilities: AbilityList,
 
+    // Flat AC, only used if there are no typed AC components.
     pub ac: i32,
     pub ab: i32,
 
@@ -110,7 +160,29 @@ pub struct Character {
     pub physical_immunity: i32,
     pub physical_dmg_reduction: i32,
 
+    #[serde(default)]
+    pub hit_points: i32,
+    #[serde(default)]
+    pub class_levels: ClassLevels,
+    // Racial types the character's favored enemy bonuses apply against.
+    #[serde(default)]
+    pub favored_enemies: Vec<RacialType>,
+    // d6s added to attacks against a defender denied its DEX AC.
+    #[serde(default)]
+    pub sneak_attack_dice: i32,
+    #[serde(default)]
+    pub death_attack_dice: i32,
+    // Smites and other abilities with limited uses per fight.
+    #[serde(default)]
+    pub activated_abilities: Vec<ActivatedAbility>,
+    // Typed AC components, `ac` is used as is if there are none.
+    #[serde(default)]
+    pub armor_class: Option<ArmorClass>,
+
     pub weapon: Weapon,
+    // Falls back to `weapon` for off-hand attacks if there is none.
+    #[serde(default)]
+    pub off_hand_weapon: Option<Weapon>,
     pub feats: Vec<Feat>,
 }
 
@@ -119,6 +191,21 @@ impl Character {
         CharacterBuilder::new()
     }
 
+    // AC against an attack made in the given context. Knocked down
+    // characters are easier to hit.
+    pub fn effective_ac(&self, context: &AttackContext) -> i32 {
+        let ac = match &self.armor_class {
+            Some(armor_class) => armor_class.effective(&self.abilities, self.is_monk(), context),
+            None => self.ac,
+        };
+
+        ac - if context.knocked_down {
+            KNOCKDOWN_AC_PENALTY
+        } else {
+            0
+        }
+    }
+
     pub fn total_apr(&self) -> i32 {
         self.base_apr + self.extra_apr + if self.is_dual_wielding() { 2 } else { 0 }
     }
@@ -127,6 +214,49 @@ impl Character {
         self.feats.contains(&feat)
     }
 
+    // Whether the character has the feat for every weapon or for the base
+    // of the weapon it wields.
+    pub fn has_weapon_feat(&self, name: &str) -> bool {
+        self.has_weapon_feat_for(name, &self.weapon.base.name)
+    }
+
+    pub fn has_weapon_feat_for(&self, name: &str, weapon_base: &str) -> bool {
+        self.has_feat(get_feat(name)) || self.has_feat(Feat::for_weapon(name, weapon_base))
+    }
+
+    // Registered feats that apply with the wielded weapon, once each even
+    // if taken both for every weapon and for the wielded one.
+    pub fn feat_effects(&self) -> Vec<Arc<FeatDefinition>> {
+        let mut result: Vec<Arc<FeatDefinition>> = vec![];
+
+        for feat in &self.feats {
+            if feat
+                .weapon_base()
+                .is_some_and(|x| x != self.weapon.base.name)
+            {
+                continue;
+            }
+
+            if let Some(definition) = get_feat_definition(feat.base_name()) {
+                if !result.iter().any(|x| x.name == definition.name) {
+                    result.push(definition);
+                }
+            }
+        }
+
+        result
+    }
+
+    // Copy of the character wielding its off-hand weapon in the main hand,
+    // used to resolve off-hand attacks. `None` if it has no off-hand weapon.
+    pub fn off_hand_view(&self) -> Option<Character> {
+        self.off_hand_weapon.as_ref().map(|weapon| Character {
+            weapon: weapon.clone(),
+            off_hand_weapon: None,
+            ..self.clone()
+        })
+    }
+
     pub fn has_blind_fight(&self) -> bool {
         self.has_feat(get_feat("Blind Fight"))
     }
@@ -140,23 +270,59 @@ impl Character {
     }
 
     pub fn has_overwhelming_critical(&self) -> bool {
-        self.has_feat(get_feat("Overwhelming Critical"))
+        self.has_weapon_feat("Overwhelming Critical")
     }
 
     pub fn has_weapon_spec(&self) -> bool {
-        self.has_feat(get_feat("Weapon Specialization"))
+        self.has_weapon_feat("Weapon Specialization")
     }
 
     pub fn has_epic_weapon_spec(&self) -> bool {
-        self.has_feat(get_feat("Epic Weapon Specialization"))
+        self.has_weapon_feat("Epic Weapon Specialization")
     }
 
     pub fn is_dual_wielding(&self) -> bool {
         self.has_feat(get_feat("Dual Wielding"))
     }
 
+    pub fn is_favored_enemy(&self, defender: &Character) -> bool {
+        self.favored_enemies.contains(&defender.race)
+    }
+
+    // Damage bonus against favored enemies, +1 and another +1 for every 5
+    // Ranger levels.
+    pub fn favored_enemy_dmg(&self, defender: &Character) -> i32 {
+        if !self.is_favored_enemy(defender) {
+            return 0;
+        }
+
+        1 + self.class_levels.level(Class::Ranger) / 5
+    }
+
+    // AB feats add against the defender on top of the character's AB.
+    pub fn ab_against(&self, defender: &Character) -> i32 {
+        self.feat_effects()
+            .iter()
+            .map(|x| x.effect.attack_bonus_against(self, defender))
+            .sum()
+    }
+
+    pub fn is_sneak_attack_immune(&self) -> bool {
+        self.feat_effects()
+            .iter()
+            .any(|x| x.effect.is_sneak_attack_immune())
+    }
+
     pub fn is_crit_immune(&self) -> bool {
-        self.has_feat(get_feat("Critical Immunity"))
+        self.feat_effects()
+            .iter()
+            .any(|x| x.effect.is_crit_immune())
+    }
+
+    pub fn dodges_first_hit(&self) -> bool {
+        self.feat_effects()
+            .iter()
+            .any(|x| x.effect.dodges_first_hit())
     }
 
     pub fn is_monk(&self) -> bool {
@@ -207,11 +373,11 @@ impl Character {
         }
 
         self.weapon.crit_multiplier()
-            + if self.has_feat(get_feat("Increased Multiplier")) {
-                1
-            } else {
-                0
-            }
+            + self
+                .feat_effects()
+                .iter()
+                .map(|x| x.effect.crit_multiplier(self))
+                .sum::<i32>()
     }
 
     pub fn weapon_threat_range(&self) -> i32 {
@@ -220,24 +386,21 @@ impl Character {
         }
 
         self.weapon.threat_range()
-            - if self.has_feat(get_feat("Improved Critical")) {
-                get_keen_increase(self.weapon.base.threat_range)
-            } else {
-                0
-            }
-            - if self.has_feat(get_feat("Ki Critical")) {
-                2
-            } else {
-                0
-            }
+            - self
+                .feat_effects()
+                .iter()
+                .map(|x| x.effect.threat_range(self))
+                .sum::<i32>()
+    }
+
+    // Weapons up to one size category larger than the character can be
+    // wielded, in two hands if they are larger.
+    pub fn can_wield(&self, weapon_base: &WeaponBase) -> bool {
+        weapon_base.size != SizeCategory::Unknown && weapon_base.size <= self.size.larger()
     }
 
     pub fn is_weapon_twohanded(&self) -> bool {
-        if self.weapon.base.size > self.size {
-            true
-        } else {
-            false
-        }
+        self.weapon.base.size > self.size
     }
 
     pub fn damage_immunity(&self, dmg_type: DamageType) -> i32 {
@@ -299,13 +462,43 @@ impl CharacterBuilder {
         self
     }
 
+    pub fn race(mut self, race: RacialType) -> Self {
+        self.character.race = race;
+        self
+    }
+
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.character.alignment = alignment;
+        self
+    }
+
+    pub fn favored_enemies(mut self, favored_enemies: Vec<RacialType>) -> Self {
+        self.character.favored_enemies = favored_enemies;
+        self
+    }
+
     pub fn abilities(mut self, abilities: AbilityList) -> Self {
         self.character.abilities = abilities;
         self
     }
 
+    // Sets the AC against attacks without a context. Typed AC components
+    // are kept and shifted by an untyped offset, so contexts that remove
+    // some of them still do. Abilities and feats must be set before calling
+    // this in that case.
     pub fn ac(mut self, ac: i32) -> Self {
-        self.character.ac = ac;
+        let current_ac = self.character.effective_ac(&AttackContext::default());
+
+        match &mut self.character.armor_class {
+            Some(armor_class) => armor_class.offset += ac - current_ac,
+            None => self.character.ac = ac,
+        }
+
+        self
+    }
+
+    pub fn armor_class(mut self, armor_class: ArmorClass) -> Self {
+        self.character.armor_class = Some(armor_class);
         self
     }
 
@@ -344,14 +537,78 @@ impl CharacterBuilder {
         self
     }
 
+    pub fn hit_points(mut self, hit_points: i32) -> Self {
+        self.character.hit_points = hit_points;
+        self
+    }
+
+    // Calculates maximum hit points from `(hit die, level count)` pairs and
+    // the CON modifier, so abilities must be set before calling this.
+    pub fn hit_points_from_levels(mut self, levels: Vec<(i32, i32)>) -> Self {
+        let con_mod = self.character.abilities.con.get_mod();
+
+        self.character.hit_points = levels
+            .iter()
+            .map(|(hit_die, level_count)| max(1, hit_die + con_mod) * level_count)
+            .sum();
+        self
+    }
+
+    // Derives AB, base APR, class feats and hit points from the class
+    // levels. Abilities must be set before calling this, AB can be
+    // overridden after it.
+    pub fn class_levels(mut self, class_levels: ClassLevels) -> Self {
+        self.character.ab = class_levels.base_attack_bonus();
+        self.character.base_apr = class_levels.base_apr();
+        self.character.class_levels = class_levels.clone();
+        self = self.add_class_feats();
+        self.character.sneak_attack_dice = class_levels.sneak_attack_dice();
+        self.character.death_attack_dice = class_levels.death_attack_dice();
+        self.hit_points_from_levels(class_levels.hit_dice())
+    }
+
+    fn add_class_feats(mut self) -> Self {
+        for feat in self.character.class_levels.feats() {
+            if !self.character.has_feat(feat.clone()) {
+                self.character.feats.push(feat);
+            }
+        }
+
+        self
+    }
+
+    // Calculates AB from its components with the given buffs. Size,
+    // abilities, feats, class levels and weapon must be set before calling
+    // this.
+    pub fn ab_from_components(mut self, buffs: Vec<(String, i32)>) -> Self {
+        self.character.ab = AttackBonusBreakdown::new(&self.character, buffs).total();
+        self
+    }
+
+    pub fn sneak_attack_dice(mut self, value: i32) -> Self {
+        self.character.sneak_attack_dice = value;
+        self
+    }
+
+    pub fn death_attack_dice(mut self, value: i32) -> Self {
+        self.character.death_attack_dice = value;
+        self
+    }
+
     pub fn weapon(mut self, weapon: Weapon) -> Self {
         self.character.weapon = weapon;
         self
     }
 
+    pub fn off_hand_weapon(mut self, weapon: Weapon) -> Self {
+        self.character.off_hand_weapon = Some(weapon);
+        self
+    }
+
+    // Feats granted by the class levels are kept.
     pub fn feats(mut self, feats: Vec<Feat>) -> Self {
         self.character.feats = feats;
-        self
+        self.add_class_feats()
     }
 
     pub fn add_feat(mut self, feat: Feat) -> Self {
@@ -359,6 +616,16 @@ impl CharacterBuilder {
         self
     }
 
+    pub fn activated_abilities(mut self, activated_abilities: Vec<ActivatedAbility>) -> Self {
+        self.character.activated_abilities = activated_abilities;
+        self
+    }
+
+    pub fn add_activated_ability(mut self, activated_ability: ActivatedAbility) -> Self {
+        self.character.activated_abilities.push(activated_ability);
+        self
+    }
+
     pub fn build(self) -> Character {
         Character { ..self.character }
     }
@@ -375,7 +642,7 @@ mod test {
     use crate::{
         character::{AbilityList, Character, CharacterBuilder},
         dice::Dice,
-        feat::feat_db::get_feat,
+        feat::feat_db::{get_feat, get_weapon_feat},
         item::{weapon_db::get_weapon_base, DamageType, ItemProperty, Weapon, WeaponBase},
         size::SizeCategory,
     };
@@ -403,7 +670,7 @@ mod test {
             .physical_damage_reduction(0)
             .weapon(Weapon::new(
                 "".into(),
-                get_weapon_base("Rapier".into()),
+                get_weapon_base("Rapier"),
                 vec![ItemProperty::Keen],
             ))
             .feats(vec![get_feat("Blind Fight")])
@@ -417,8 +684,8 @@ mod test {
         assert_eq!(character.abilities.cha.get_mod(), -2);
 
         assert_eq!(character.total_apr(), 5);
-        assert_eq!(character.has_blind_fight(), true);
-        assert_eq!(character.is_weapon_twohanded(), false);
+        assert!(character.has_blind_fight());
+        assert!(!character.is_weapon_twohanded());
 
         // Keen + Improved Critical test: 18-20
         let character = CharacterBuilder::from(character)
@@ -546,13 +813,43 @@ mod test {
             ])
             .build();
 
-        assert_eq!(character.is_dual_wielding(), true);
-        assert_eq!(character.is_crit_immune(), true);
+        assert!(character.is_dual_wielding());
+        assert!(character.is_crit_immune());
         assert_eq!(character.total_apr(), 8);
-        assert_eq!(character.is_weapon_twohanded(), true);
+        assert!(character.is_weapon_twohanded());
         assert_eq!(character.weapon_crit_multiplier(), 3);
-        assert_eq!(character.has_overwhelming_critical(), true);
-        assert_eq!(character.has_bane_of_enemies(), true);
-        assert_eq!(character.has_epic_dodge(), true);
+        assert!(character.has_overwhelming_critical());
+        assert!(character.has_bane_of_enemies());
+        assert!(character.has_epic_dodge());
+
+        let character = Character::builder()
+            .abilities(AbilityList::builder().con(28).build())
+            .hit_points_from_levels(vec![(10, 10), (12, 20)])
+            .build();
+        assert_eq!(character.hit_points, 19 * 10 + 21 * 20);
+
+        let character = Character::builder()
+            .abilities(AbilityList::builder().con(1).build())
+            .hit_points_from_levels(vec![(4, 3)])
+            .build();
+        assert_eq!(character.hit_points, 3);
+
+        let character = Character::builder()
+            .feats(vec![
+                get_weapon_feat("Weapon Specialization", "Scimitar"),
+                get_weapon_feat("Improved Critical", "Dagger"),
+            ])
+            .weapon(Weapon::new("".into(), get_weapon_base("Scimitar"), vec![]))
+            .off_hand_weapon(Weapon::new("".into(), get_weapon_base("Dagger"), vec![]))
+            .build();
+        assert_eq!(character.feats[0].base_name(), "Weapon Specialization");
+        assert_eq!(character.feats[0].weapon_base(), Some("Scimitar"));
+        assert!(character.has_weapon_spec());
+        assert_eq!(character.weapon_threat_range(), 18);
+
+        let off_hand = character.off_hand_view().unwrap();
+        assert!(!off_hand.has_weapon_spec());
+        assert_eq!(off_hand.weapon_threat_range(), 17);
+        assert!(off_hand.off_hand_weapon.is_none());
     }
 }
@@ -0,0 +1,341 @@
+use super::{
+    feat::{feat_db::get_feat, Feat},
+    rules::{EPIC_LEVEL, MAX_BASE_APR, MAX_MONK_BASE_APR},
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::min;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BabProgression {
+    Full,
+    ThreeQuarters,
+    Half,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Class {
+    ArcaneArcher,
+    Assassin,
+    Barbarian,
+    Bard,
+    Blackguard,
+    ChampionOfTorm,
+    Cleric,
+    DragonDisciple,
+    Druid,
+    DwarvenDefender,
+    Fighter,
+    HarperScout,
+    Monk,
+    PaleMaster,
+    Paladin,
+    PurpleDragonKnight,
+    Ranger,
+    Rogue,
+    Shadowdancer,
+    Shifter,
+    Sorcerer,
+    WeaponMaster,
+    Wizard,
+}
+
+impl Class {
+    pub fn hit_die(&self) -> i32 {
+        match self {
+            Self::Barbarian | Self::DragonDisciple | Self::DwarvenDefender => 12,
+            Self::Blackguard
+            | Self::ChampionOfTorm
+            | Self::Fighter
+            | Self::Paladin
+            | Self::PurpleDragonKnight
+            | Self::Ranger
+            | Self::WeaponMaster => 10,
+            Self::ArcaneArcher
+            | Self::Cleric
+            | Self::Druid
+            | Self::Monk
+            | Self::Shadowdancer
+            | Self::Shifter => 8,
+            Self::Assassin | Self::Bard | Self::HarperScout | Self::PaleMaster | Self::Rogue => 6,
+            Self::Sorcerer | Self::Wizard => 4,
+        }
+    }
+
+    pub fn bab_progression(&self) -> BabProgression {
+        match self {
+            Self::ArcaneArcher
+            | Self::Barbarian
+            | Self::Blackguard
+            | Self::ChampionOfTorm
+            | Self::DwarvenDefender
+            | Self::Fighter
+            | Self::Paladin
+            | Self::PurpleDragonKnight
+            | Self::Ranger
+            | Self::WeaponMaster => BabProgression::Full,
+            Self::Assassin
+            | Self::Bard
+            | Self::Cleric
+            | Self::DragonDisciple
+            | Self::Druid
+            | Self::HarperScout
+            | Self::Monk
+            | Self::Rogue
+            | Self::Shadowdancer
+            | Self::Shifter => BabProgression::ThreeQuarters,
+            Self::PaleMaster | Self::Sorcerer | Self::Wizard => BabProgression::Half,
+        }
+    }
+
+    // Base attack bonus the class grants at the given (non-epic) level.
+    pub fn base_attack_bonus(&self, level: i32) -> i32 {
+        match self.bab_progression() {
+            BabProgression::Full => level,
+            BabProgression::ThreeQuarters => level * 3 / 4,
+            BabProgression::Half => level / 2,
+        }
+    }
+
+    // Feats granted automatically at the given class level.
+    pub fn feats(&self, level: i32) -> Vec<Feat> {
+        let mut feats = vec![];
+
+        match self {
+            Self::Monk => feats.push(get_feat("Monk")),
+            Self::WeaponMaster => {
+                if level >= 5 {
+                    feats.push(get_feat("Increased Multiplier"));
+                }
+
+                if level >= 7 {
+                    feats.push(get_feat("Ki Critical"));
+                }
+            }
+            _ => (),
+        }
+
+        feats
+    }
+}
+
+impl std::fmt::Display for Class {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::ArcaneArcher => "Arcane Archer",
+            Self::Assassin => "Assassin",
+            Self::Barbarian => "Barbarian",
+            Self::Bard => "Bard",
+            Self::Blackguard => "Blackguard",
+            Self::ChampionOfTorm => "Champion of Torm",
+            Self::Cleric => "Cleric",
+            Self::DragonDisciple => "Dragon Disciple",
+            Self::Druid => "Druid",
+            Self::DwarvenDefender => "Dwarven Defender",
+            Self::Fighter => "Fighter",
+            Self::HarperScout => "Harper Scout",
+            Self::Monk => "Monk",
+            Self::PaleMaster => "Pale Master",
+            Self::Paladin => "Paladin",
+            Self::PurpleDragonKnight => "Purple Dragon Knight",
+            Self::Ranger => "Ranger",
+            Self::Rogue => "Rogue",
+            Self::Shadowdancer => "Shadowdancer",
+            Self::Shifter => "Shifter",
+            Self::Sorcerer => "Sorcerer",
+            Self::WeaponMaster => "Weapon Master",
+            Self::Wizard => "Wizard",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+// Class levels of a character, in the order they were taken. Only the
+// first `EPIC_LEVEL` levels follow the class BAB progressions, every
+// second level after them adds +1 BAB regardless of the class.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClassLevels(pub Vec<(Class, i32)>);
+
+impl ClassLevels {
+    pub fn total_level(&self) -> i32 {
+        self.0.iter().map(|(_, level)| level).sum()
+    }
+
+    pub fn level(&self, class: Class) -> i32 {
+        self.0
+            .iter()
+            .filter(|(x, _)| *x == class)
+            .map(|(_, level)| level)
+            .sum()
+    }
+
+    // Levels of each class taken before reaching the epic level.
+    fn pre_epic_levels(&self) -> Vec<(Class, i32)> {
+        let mut remaining = EPIC_LEVEL;
+        let mut levels: Vec<(Class, i32)> = vec![];
+
+        for (class, level) in &self.0 {
+            let level = min(*level, remaining);
+            remaining -= level;
+
+            match levels.iter_mut().find(|(x, _)| x == class) {
+                Some(entry) => entry.1 += level,
+                None => levels.push((*class, level)),
+            }
+        }
+
+        levels
+    }
+
+    fn pre_epic_base_attack_bonus(&self) -> i32 {
+        self.pre_epic_levels()
+            .iter()
+            .map(|(class, level)| class.base_attack_bonus(*level))
+            .sum()
+    }
+
+    pub fn base_attack_bonus(&self) -> i32 {
+        let epic_levels = (self.total_level() - EPIC_LEVEL).max(0);
+
+        self.pre_epic_base_attack_bonus() + (epic_levels + 1) / 2
+    }
+
+    // Attacks per round from the BAB before epic levels, one for every 5
+    // points or for every 3 points with monk levels.
+    pub fn base_apr(&self) -> i32 {
+        let bab = self.pre_epic_base_attack_bonus();
+
+        if bab < 1 {
+            return 1;
+        }
+
+        if self.level(Class::Monk) > 0 {
+            min(MAX_MONK_BASE_APR, (bab - 1) / 3 + 1)
+        } else {
+            min(MAX_BASE_APR, (bab - 1) / 5 + 1)
+        }
+    }
+
+    pub fn feats(&self) -> Vec<Feat> {
+        let mut feats: Vec<Feat> = vec![];
+
+        for (class, _) in &self.0 {
+            for feat in class.feats(self.level(*class)) {
+                if !feats.contains(&feat) {
+                    feats.push(feat);
+                }
+            }
+        }
+
+        feats
+    }
+
+    // Sneak attack d6s from Rogue and Blackguard levels. Rogues gain one on
+    // every odd level, Blackguards on levels 4, 7, 10 and so on.
+    pub fn sneak_attack_dice(&self) -> i32 {
+        let rogue = self.level(Class::Rogue);
+        let blackguard = self.level(Class::Blackguard);
+
+        (rogue + 1) / 2
+            + if blackguard >= 4 {
+                (blackguard - 1) / 3
+            } else {
+                0
+            }
+    }
+
+    // Death attack d6s, one on every odd Assassin level.
+    pub fn death_attack_dice(&self) -> i32 {
+        (self.level(Class::Assassin) + 1) / 2
+    }
+
+    // `(hit die, level count)` pairs of the classes.
+    pub fn hit_dice(&self) -> Vec<(i32, i32)> {
+        self.0
+            .iter()
+            .map(|(class, level)| (class.hit_die(), *level))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for ClassLevels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.0
+                .iter()
+                .map(|(class, level)| format!("{} {}", level, class))
+                .collect::<Vec<String>>()
+                .join(" / ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Class, ClassLevels};
+    use crate::{
+        character::{AbilityList, Character},
+        feat::feat_db::get_feat,
+    };
+
+    #[test]
+    fn class_levels() {
+        let levels = ClassLevels(vec![(Class::Fighter, 15), (Class::WeaponMaster, 7)]);
+        assert_eq!(levels.total_level(), 22);
+        // 15 fighter + 5 weapon master levels before epic, then +1.
+        assert_eq!(levels.base_attack_bonus(), 21);
+        assert_eq!(levels.base_apr(), 4);
+        assert_eq!(
+            levels.feats(),
+            vec![get_feat("Increased Multiplier"), get_feat("Ki Critical")]
+        );
+        assert_eq!(levels.to_string(), "15 Fighter / 7 Weapon Master");
+
+        let levels = ClassLevels(vec![(Class::Wizard, 10)]);
+        assert_eq!(levels.base_attack_bonus(), 5);
+        assert_eq!(levels.base_apr(), 1);
+
+        let levels = ClassLevels(vec![(Class::Monk, 30), (Class::Rogue, 10)]);
+        assert_eq!(levels.base_attack_bonus(), 15 + 10);
+        assert_eq!(levels.base_apr(), 5);
+        assert_eq!(levels.level(Class::Rogue), 10);
+        assert_eq!(levels.sneak_attack_dice(), 5);
+
+        let levels = ClassLevels(vec![
+            (Class::Rogue, 3),
+            (Class::Blackguard, 10),
+            (Class::Assassin, 5),
+        ]);
+        assert_eq!(levels.sneak_attack_dice(), 2 + 3);
+        assert_eq!(levels.death_attack_dice(), 3);
+
+        let character = Character::builder()
+            .abilities(AbilityList::builder().con(14).build())
+            .feats(vec![get_feat("Increased Multiplier")])
+            .class_levels(ClassLevels(vec![
+                (Class::Fighter, 10),
+                (Class::WeaponMaster, 7),
+                (Class::Barbarian, 3),
+            ]))
+            .build();
+
+        assert_eq!(character.ab, 20);
+        assert_eq!(character.base_apr, 4);
+        assert_eq!(character.hit_points, 12 * 10 + 12 * 7 + 14 * 3);
+        assert_eq!(character.feats.len(), 2);
+        assert!(character.has_feat(get_feat("Ki Critical")));
+        assert_eq!(character.class_levels.level(Class::WeaponMaster), 7);
+
+        // Setting the feats afterwards keeps the class feats.
+        let character = Character::builder()
+            .class_levels(character.class_levels)
+            .feats(vec![get_feat("Dual Wielding")])
+            .build();
+
+        assert_eq!(character.feats.len(), 3);
+        assert!(character.has_feat(get_feat("Ki Critical")));
+        assert!(character.has_feat(get_feat("Increased Multiplier")));
+    }
+}
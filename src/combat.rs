@@ -1,14 +1,18 @@
 use super::{
+    activated_ability::{AbilityState, ActivatedAbility},
     character::Character,
+    damage_hook::{DamageHookContext, DamageHooks, DamageStage},
+    damage_source::{DamageBreakdown, DamageSource, DamageSourceStatistics},
     dice::Dice,
+    feat::FeatDefinition,
     item::{DamageResult, ItemProperty},
+    statistics::RunningStatistics,
 };
 use crate::string::align_string;
-use serde::{Serialize, Deserialize};
-use std::cmp::max;
+use serde::{Deserialize, Serialize};
+use std::{cmp::max, sync::Arc};
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-#[allow(unused)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum HitResult {
     Hit,
     CriticalHit,
@@ -17,20 +21,12 @@ pub enum HitResult {
     EpicDodged,
 }
 
-#[allow(unused)]
 impl HitResult {
     pub fn is_missed(&self) -> bool {
-        match *self {
-            Self::Miss | Self::TargetConcealed | Self::EpicDodged => true,
-            _ => false,
-        }
+        matches!(*self, Self::Miss | Self::TargetConcealed | Self::EpicDodged)
     }
     pub fn is_crit(&self) -> bool {
-        if *self == Self::CriticalHit {
-            true
-        } else {
-            false
-        }
+        *self == Self::CriticalHit
     }
 }
 
@@ -55,7 +51,7 @@ impl std::fmt::Display for AttackType {
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AttackInfo {
     pub ab: i32,
     pub type_: AttackType,
@@ -67,6 +63,114 @@ impl AttackInfo {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttackSlotStatistics {
+    pub atk_no: i32,
+    pub type_: AttackType,
+    pub ab: i32,
+    pub total_attacks: i64,
+    pub total_hits: i64,
+    pub critical_hits: i64,
+    pub concealed_attacks: i64,
+    pub epic_dodged_attacks: i64,
+    pub dmg_dealt: i64,
+}
+
+impl AttackSlotStatistics {
+    pub fn new(atk_no: i32, atk_info: &AttackInfo) -> Self {
+        Self {
+            atk_no,
+            type_: atk_info.type_,
+            ab: atk_info.ab,
+            total_attacks: 0,
+            total_hits: 0,
+            critical_hits: 0,
+            concealed_attacks: 0,
+            epic_dodged_attacks: 0,
+            dmg_dealt: 0,
+        }
+    }
+
+    pub fn add(&mut self, hit_result: &HitResult, dmg: i32) {
+        self.total_attacks += 1;
+
+        match hit_result {
+            HitResult::Hit => self.total_hits += 1,
+            HitResult::CriticalHit => {
+                self.total_hits += 1;
+                self.critical_hits += 1;
+            }
+            HitResult::TargetConcealed => self.concealed_attacks += 1,
+            HitResult::EpicDodged => self.epic_dodged_attacks += 1,
+            HitResult::Miss => (),
+        }
+
+        self.dmg_dealt += dmg as i64;
+    }
+
+    pub fn add_from(&mut self, other: &AttackSlotStatistics) {
+        self.total_attacks += other.total_attacks;
+        self.total_hits += other.total_hits;
+        self.critical_hits += other.critical_hits;
+        self.concealed_attacks += other.concealed_attacks;
+        self.epic_dodged_attacks += other.epic_dodged_attacks;
+        self.dmg_dealt += other.dmg_dealt;
+    }
+
+    pub fn total_misses(&self) -> i64 {
+        self.total_attacks - self.total_hits
+    }
+
+    fn rate(&self, count: i64) -> f64 {
+        if self.total_attacks == 0 {
+            return 0.0;
+        }
+
+        count as f64 / self.total_attacks as f64
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        self.rate(self.total_hits)
+    }
+
+    pub fn crit_rate(&self) -> f64 {
+        self.rate(self.critical_hits)
+    }
+
+    pub fn concealment_rate(&self) -> f64 {
+        self.rate(self.concealed_attacks)
+    }
+
+    pub fn dmg_per_attack(&self) -> f64 {
+        self.rate(self.dmg_dealt)
+    }
+}
+
+impl std::fmt::Display for AttackSlotStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let type_ = match self.type_ {
+            AttackType::MainHand => "MAIN",
+            AttackType::OffHand => "OFF",
+            AttackType::Extra => "EXTRA",
+        };
+
+        write!(
+            f,
+            "{}",
+            align_string(
+                format!("    * #{} {} (AB {})", self.atk_no, type_, self.ab).as_str(),
+                format!(
+                    "HIT {:.2}% / CRIT {:.2}% / CONCEALED {:.2}% / DMG {:.2}",
+                    self.hit_rate() * 100.0,
+                    self.crit_rate() * 100.0,
+                    self.concealment_rate() * 100.0,
+                    self.dmg_per_attack()
+                )
+            )
+        )
+    }
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct CombatStatistics {
     pub total_hits: i64,
@@ -75,6 +179,24 @@ pub struct CombatStatistics {
     pub concealed_attacks: i64,
     pub epic_dodged_attacks: i64,
     pub dmg_dealt: DamageResult,
+    #[serde(default)]
+    pub dmg_per_round: RunningStatistics,
+    #[serde(default)]
+    pub attack_slots: Vec<AttackSlotStatistics>,
+    #[serde(default)]
+    pub dmg_sources: Vec<DamageSourceStatistics>,
+    // Hits that rolled in the weapon's threat range.
+    #[serde(default)]
+    pub critical_threats: i64,
+    // Damage dealt by critical hits.
+    #[serde(default)]
+    pub crit_dmg: i64,
+    // Confirmed critical hits that were negated by critical immunity and
+    // the extra damage they would have dealt.
+    #[serde(default)]
+    pub crits_prevented: i64,
+    #[serde(default)]
+    pub crit_immunity_dmg_prevented: i64,
 }
 
 impl CombatStatistics {
@@ -85,36 +207,256 @@ impl CombatStatistics {
     pub fn total_attacks(&self) -> i64 {
         self.total_hits + self.total_misses
     }
+
+    pub fn total_rounds(&self) -> i64 {
+        self.dmg_per_round.count()
+    }
+
+    pub fn dmg_per_round_string(&self) -> String {
+        let dpr = &self.dmg_per_round;
+        let (ci_low, ci_high) = dpr.confidence_interval();
+
+        [
+            align_string("AVERAGE DAMAGE PER ROUND", format!("{:.2}", dpr.mean())),
+            align_string("    * STANDARD DEVIATION", format!("{:.2}", dpr.std_dev())),
+            align_string("    * STANDARD ERROR", format!("{:.2}", dpr.std_error())),
+            align_string(
+                "    * 95% CONFIDENCE INTERVAL",
+                format!("{:.2} - {:.2}", ci_low, ci_high),
+            ),
+            align_string("    * MIN / MAX", format!("{} / {}", dpr.min(), dpr.max())),
+        ]
+        .join("\n")
+    }
+
+    pub(crate) fn add_attack(
+        &mut self,
+        atk_no: i32,
+        atk_info: &AttackInfo,
+        attack_result: &AttackResult,
+    ) {
+        let hit_result = &attack_result.hit_result;
+        let dmg_breakdown = &attack_result.dmg_breakdown;
+        let dmg_result = dmg_breakdown.to_damage_result();
+
+        match hit_result {
+            HitResult::Hit => self.total_hits += 1,
+            HitResult::CriticalHit => {
+                self.total_hits += 1;
+                self.critical_hits += 1;
+            }
+            HitResult::Miss => self.total_misses += 1,
+            HitResult::TargetConcealed => {
+                self.total_misses += 1;
+                self.concealed_attacks += 1;
+            }
+            HitResult::EpicDodged => {
+                self.total_misses += 1;
+                self.epic_dodged_attacks += 1;
+            }
+        }
+
+        self.dmg_dealt.add_from(&dmg_result);
+
+        if attack_result.is_threat {
+            self.critical_threats += 1;
+        }
+
+        if *hit_result == HitResult::CriticalHit {
+            self.crit_dmg += dmg_result.total_dmg() as i64;
+        }
+
+        if let Some(prevented) = attack_result.crit_immunity_prevented {
+            self.crits_prevented += 1;
+            self.crit_immunity_dmg_prevented += prevented as i64;
+        }
+
+        for entry in dmg_breakdown.entries() {
+            self.dmg_source_statistics_mut(&entry.source)
+                .add_entry(entry);
+        }
+
+        let i = (atk_no - 1) as usize;
+
+        while self.attack_slots.len() <= i {
+            let slot_atk_no = self.attack_slots.len() as i32 + 1;
+            self.attack_slots
+                .push(AttackSlotStatistics::new(slot_atk_no, atk_info));
+        }
+
+        self.attack_slots[i].add(hit_result, dmg_result.total_dmg());
+    }
+
+    // Ratio of hits that rolled in the weapon's threat range.
+    pub fn threat_rate(&self) -> f64 {
+        if self.total_hits == 0 {
+            return 0.0;
+        }
+
+        self.critical_threats as f64 / self.total_hits as f64
+    }
+
+    // Ratio of threats that were confirmed, including the ones negated by
+    // critical immunity.
+    pub fn crit_confirmation_rate(&self) -> f64 {
+        if self.critical_threats == 0 {
+            return 0.0;
+        }
+
+        (self.critical_hits + self.crits_prevented) as f64 / self.critical_threats as f64
+    }
+
+    pub fn normal_hit_dmg(&self) -> i64 {
+        self.dmg_dealt.total_dmg() as i64 - self.crit_dmg
+    }
+
+    pub fn crit_dmg_share(&self) -> f64 {
+        let total_dmg = self.dmg_dealt.total_dmg();
+
+        if total_dmg == 0 {
+            return 0.0;
+        }
+
+        self.crit_dmg as f64 / total_dmg as f64
+    }
+
+    pub fn crit_string(&self) -> String {
+        let normal_hits = self.total_hits - self.critical_hits;
+        let per_hit = |dmg: i64, hits: i64| {
+            if hits == 0 {
+                0.0
+            } else {
+                dmg as f64 / hits as f64
+            }
+        };
+
+        [
+            "CRITICAL HITS".into(),
+            align_string(
+                "    * THREAT RATE",
+                format!("{:.2}%", self.threat_rate() * 100.0),
+            ),
+            align_string(
+                "    * CONFIRMATION RATE",
+                format!("{:.2}%", self.crit_confirmation_rate() * 100.0),
+            ),
+            align_string(
+                "    * DAMAGE SHARE",
+                format!("{:.2}%", self.crit_dmg_share() * 100.0),
+            ),
+            align_string(
+                "    * DAMAGE PER CRITICAL HIT",
+                format!("{:.2}", per_hit(self.crit_dmg, self.critical_hits)),
+            ),
+            align_string(
+                "    * DAMAGE PER NORMAL HIT",
+                format!("{:.2}", per_hit(self.normal_hit_dmg(), normal_hits)),
+            ),
+            align_string(
+                "    * PREVENTED BY IMMUNITY",
+                format!(
+                    "{} ({:.2} DPR)",
+                    self.crits_prevented,
+                    self.crit_immunity_dmg_prevented as f64 / max(1, self.total_rounds()) as f64
+                ),
+            ),
+        ]
+        .join("\n")
+    }
+
+    fn dmg_source_statistics_mut(&mut self, source: &DamageSource) -> &mut DamageSourceStatistics {
+        let i = match self.dmg_sources.iter().position(|x| x.source == *source) {
+            Some(i) => i,
+            None => {
+                self.dmg_sources
+                    .push(DamageSourceStatistics::new(source.clone()));
+                self.dmg_sources.len() - 1
+            }
+        };
+
+        &mut self.dmg_sources[i]
+    }
+
+    pub fn dmg_source_statistics(&self, source: DamageSource) -> Option<&DamageSourceStatistics> {
+        self.dmg_sources.iter().find(|x| x.source == source)
+    }
+
+    pub fn dmg_sources_string(&self) -> String {
+        let total_rounds = max(1, self.total_rounds()) as f64;
+        let total_dealt = max(1, self.dmg_dealt.total_dmg()) as f64;
+        let mut string_list: Vec<String> = vec!["DAMAGE SOURCES".into()];
+
+        for source in &self.dmg_sources {
+            string_list.push(align_string(
+                format!("    * {}", source.source.to_string().to_uppercase()).as_str(),
+                format!(
+                    "{:.2} DPR ({:.2}%) / IMMUNITY -{:.2} / REDUCTION -{:.2}",
+                    source.dealt() as f64 / total_rounds,
+                    source.dealt() as f64 / total_dealt * 100.0,
+                    source.immunity as f64 / total_rounds,
+                    source.reduction as f64 / total_rounds
+                ),
+            ));
+        }
+
+        string_list.join("\n")
+    }
+
+    // Combines the statistics of every attack slot with the given attack
+    // type. Attack number and AB are taken from the first matching slot.
+    pub fn attack_type_statistics(&self, type_: AttackType) -> Option<AttackSlotStatistics> {
+        let mut slots = self.attack_slots.iter().filter(|x| x.type_ == type_);
+        let mut statistics = slots.next()?.clone();
+
+        for slot in slots {
+            statistics.add_from(slot);
+        }
+
+        Some(statistics)
+    }
+
+    pub fn add_from(&mut self, other: &CombatStatistics) {
+        self.total_hits += other.total_hits;
+        self.total_misses += other.total_misses;
+        self.concealed_attacks += other.concealed_attacks;
+        self.epic_dodged_attacks += other.epic_dodged_attacks;
+        self.critical_hits += other.critical_hits;
+        self.dmg_dealt.add_from(&other.dmg_dealt);
+        self.dmg_per_round.merge(&other.dmg_per_round);
+        self.critical_threats += other.critical_threats;
+        self.crit_dmg += other.crit_dmg;
+        self.crits_prevented += other.crits_prevented;
+        self.crit_immunity_dmg_prevented += other.crit_immunity_dmg_prevented;
+
+        for source in &other.dmg_sources {
+            self.dmg_source_statistics_mut(&source.source)
+                .add_from(source);
+        }
+
+        for (i, slot) in other.attack_slots.iter().enumerate() {
+            match self.attack_slots.get_mut(i) {
+                Some(self_slot) => self_slot.add_from(slot),
+                None => self.attack_slots.push(slot.clone()),
+            }
+        }
+    }
 }
 
-impl ToString for CombatStatistics {
-    fn to_string(&self) -> String {
-        let mut string_list: Vec<String> = vec![];
-
-        string_list.push(align_string(
-            "TOTAL ATTACK",
-            self.total_attacks().to_string(),
-        ));
-        string_list.push(align_string("TOTAL HIT", self.total_hits.to_string()));
-        string_list.push(align_string(
-            "    * CRITICAL HIT",
-            self.critical_hits.to_string(),
-        ));
-        string_list.push("".into());
-        string_list.push(align_string("TOTAL MISS", self.total_misses.to_string()));
-        string_list.push(align_string(
-            "    * CONCEALED",
-            self.concealed_attacks.to_string(),
-        ));
-        string_list.push(align_string(
-            "    * EPIC DODGED",
-            self.epic_dodged_attacks.to_string(),
-        ));
-        string_list.push("".into());
-        string_list.push(align_string(
-            "TOTAL DAMAGE",
-            self.dmg_dealt.total_dmg().to_string(),
-        ));
+impl std::fmt::Display for CombatStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string_list: Vec<String> = vec![
+            align_string("TOTAL ROUND", self.total_rounds().to_string()),
+            "".into(),
+            align_string("TOTAL ATTACK", self.total_attacks().to_string()),
+            align_string("TOTAL HIT", self.total_hits.to_string()),
+            align_string("    * CRITICAL HIT", self.critical_hits.to_string()),
+            "".into(),
+            align_string("TOTAL MISS", self.total_misses.to_string()),
+            align_string("    * CONCEALED", self.concealed_attacks.to_string()),
+            align_string("    * EPIC DODGED", self.epic_dodged_attacks.to_string()),
+            "".into(),
+            align_string("TOTAL DAMAGE", self.dmg_dealt.total_dmg().to_string()),
+        ];
 
         for type_ in self.dmg_dealt.get_types_sorted() {
             string_list.push(align_string(
@@ -123,49 +465,252 @@ impl ToString for CombatStatistics {
             ));
         }
 
-        string_list.join("\n")
+        if self.total_hits > 0 {
+            string_list.push("".into());
+            string_list.push(self.crit_string());
+        }
+
+        if !self.dmg_sources.is_empty() {
+            string_list.push("".into());
+            string_list.push(self.dmg_sources_string());
+        }
+
+        if !self.attack_slots.is_empty() {
+            string_list.push("".into());
+            string_list.push("ATTACK BREAKDOWN".into());
+
+            for slot in &self.attack_slots {
+                string_list.push(slot.to_string());
+            }
+        }
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+// Defender state that only lasts for a single round. It is shared by every
+// attacker hitting the same defender in that round.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RoundState {
+    pub defender_can_epic_dodge: bool,
+}
+
+impl Default for RoundState {
+    fn default() -> Self {
+        Self {
+            defender_can_epic_dodge: true,
+        }
+    }
+}
+
+// Everything that happened in a single attack.
+pub(crate) struct AttackResult {
+    pub hit_result: HitResult,
+    pub dmg_breakdown: DamageBreakdown,
+    // Hit roll was in the weapon's threat range.
+    pub is_threat: bool,
+    // Extra damage a confirmed critical hit would have dealt if the
+    // defender wasn't immune to critical hits.
+    pub crit_immunity_prevented: Option<i32>,
+}
+
+// Damage of a hit and, for a critical hit critical immunity stopped, how
+// much more the critical hit would have dealt.
+struct HitDamage {
+    dmg_breakdown: DamageBreakdown,
+    crit_immunity_prevented: Option<i32>,
+}
+
+impl AttackResult {
+    fn new(hit_result: HitResult) -> Self {
+        Self {
+            hit_result,
+            dmg_breakdown: DamageBreakdown::new(),
+            is_threat: false,
+            crit_immunity_prevented: None,
+        }
+    }
+}
+
+// Circumstances of the attacks that change the defender's effective AC.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AttackContext {
+    pub flat_footed: bool,
+    pub flanked: bool,
+    // Ignores armor, shield and natural AC.
+    pub touch: bool,
+    pub knocked_down: bool,
+}
+
+impl AttackContext {
+    // Defender loses its DEX and dodge AC.
+    pub fn is_denied_dex(&self) -> bool {
+        self.flat_footed || self.flanked
+    }
+}
+
+// Feats of the character making the attacks of a hand and what they add up
+// to against the defender, resolved once per combat.
+struct HandProfile {
+    feat_effects: Vec<Arc<FeatDefinition>>,
+    ab_against: i32,
+    threat_range: i32,
+    crit_multiplier: i32,
+    // Defender's concealment as it applies to the hand's attacks.
+    concealment: f32,
+}
+
+impl HandProfile {
+    fn new(attacker: &Character, defender: &Character) -> Self {
+        Self {
+            feat_effects: attacker.feat_effects(),
+            ab_against: attacker.ab_against(defender),
+            threat_range: attacker.weapon_threat_range(),
+            crit_multiplier: attacker.weapon_crit_multiplier(),
+            concealment: Combat::resolve_concealment(attacker, defender),
+        }
     }
 }
 
 pub struct Combat<'a> {
     attacker: &'a Character,
     defender: &'a Character,
+    off_hand_attacker: Option<Character>,
+    main_hand: HandProfile,
+    off_hand: Option<HandProfile>,
+    damage_hooks: DamageHooks,
+    defender_crit_immune: bool,
+    defender_dodges_first_hit: bool,
+    // Defender's AC in the attack context.
+    defender_ac: i32,
+    // Whether the context lets sneak and death attack dice apply.
+    is_sneak_attack: bool,
 }
 
 impl<'a> Combat<'a> {
     pub fn new(attacker: &'a Character, defender: &'a Character) -> Self {
-        Self { attacker, defender }
+        Self::with_context(attacker, defender, AttackContext::default())
+    }
+
+    pub fn with_context(
+        attacker: &'a Character,
+        defender: &'a Character,
+        context: AttackContext,
+    ) -> Self {
+        let off_hand_attacker = attacker.off_hand_view();
+
+        Self {
+            attacker,
+            defender,
+            main_hand: HandProfile::new(attacker, defender),
+            off_hand: off_hand_attacker
+                .as_ref()
+                .map(|x| HandProfile::new(x, defender)),
+            off_hand_attacker,
+            damage_hooks: DamageHooks::registered(),
+            defender_crit_immune: defender.is_crit_immune(),
+            defender_dodges_first_hit: defender.dodges_first_hit(),
+            defender_ac: defender.effective_ac(&context),
+            is_sneak_attack: context.is_denied_dex() && !defender.is_sneak_attack_immune(),
+        }
+    }
+
+    pub fn attacker(&self) -> &'a Character {
+        self.attacker
+    }
+
+    pub fn defender(&self) -> &'a Character {
+        self.defender
     }
 
     // Returns the final concealment of defender after various
     // factors are considered.
     fn resolve_concealment(attacker: &Character, defender: &Character) -> f32 {
-        if attacker.has_blind_fight() {
-            (defender.concealment.pow(2) as f32) / 100.0
-        } else {
-            defender.concealment as f32
+        attacker
+            .feat_effects()
+            .iter()
+            .fold(defender.concealment as f32, |concealment, x| {
+                x.effect.concealment(concealment)
+            })
+    }
+
+    // Applies the defender's damage immunity and reduction to every damage
+    // type of the breakdown.
+    fn apply_mitigation(defender: &Character, dmg_breakdown: &mut DamageBreakdown) {
+        let dmg_result = dmg_breakdown.to_damage_result();
+        let dmg_types = dmg_result.get_types();
+
+        for dmg_type in dmg_types {
+            let defender_dmg_immunity = defender.damage_immunity(dmg_type);
+            let defender_dmg_reduction = defender.damage_reduction(dmg_type);
+            let dmg_before_mitigation = dmg_result.get(dmg_type);
+
+            if defender_dmg_immunity > 0 {
+                dmg_result.sub(
+                    dmg_type,
+                    dmg_result.get(dmg_type) * defender_dmg_immunity / 100,
+                );
+            }
+
+            let dmg_after_immunity = dmg_result.get(dmg_type);
+
+            if defender_dmg_reduction > 0 {
+                dmg_result.sub(dmg_type, defender_dmg_reduction);
+            }
+
+            dmg_breakdown.distribute_mitigation(
+                dmg_type,
+                dmg_before_mitigation - dmg_after_immunity,
+                dmg_after_immunity - dmg_result.get(dmg_type),
+            );
         }
     }
 
-    fn resolve_damage(
-        attacker: &Character,
-        defender: &Character,
+    // Rolls the damage of a hit. `is_crit_prevented` is for a confirmed
+    // critical hit critical immunity stopped: what the critical hit would
+    // have added is estimated from the same rolls, multiplying the damage
+    // critical hits multiply and rolling only what critical hits add.
+    fn resolve_damage_breakdown(
+        &self,
         atk_info: AttackInfo,
         is_crit: bool,
-    ) -> DamageResult {
-        let dmg_result = DamageResult::new();
-
-        let multiplier = if !is_crit {
-            1
-        } else {
-            attacker.weapon_crit_multiplier()
-        };
+        is_crit_prevented: bool,
+        abilities: &[&ActivatedAbility],
+    ) -> HitDamage {
+        let attacker = self.attacker_for(atk_info.type_);
+        let hand = self.hand_for(atk_info.type_);
+        let defender = self.defender;
+        let is_sneak_attack = self.is_sneak_attack;
+        let mut dmg_breakdown = DamageBreakdown::new();
+
+        let crit_multiplier = hand.crit_multiplier;
+        let multiplier = if !is_crit { 1 } else { crit_multiplier };
 
         // TODO: Get the damage type of weapon that defender has less immunity / reduction / resistance
         //       against if weapon has multiple damage types.
         // TODO: Add unarmed support. Currently if there is no weapon provided to character,
         //       Rust panics because of unwrapping weapon damage type which is null.
         let weapon_base_dmg_type = *attacker.weapon.base.damage_type.first().unwrap();
+        let context = DamageHookContext {
+            attacker,
+            defender,
+            atk_info,
+            is_crit,
+            is_sneak_attack,
+        };
+
+        for _ in 0..multiplier {
+            let mut hook_dmg_breakdown = DamageBreakdown::new();
+            self.damage_hooks.run(
+                DamageStage::PreMultiplier,
+                &context,
+                &mut hook_dmg_breakdown,
+            );
+
+            for entry in hook_dmg_breakdown.entries() {
+                dmg_breakdown.add(entry.source.clone(), entry.type_, entry.amount);
+            }
+        }
 
         // STR mod
         let str_mod_bonus = ((attacker.abilities.str.get_mod()
@@ -182,144 +727,271 @@ impl<'a> Combat<'a> {
             })
             * multiplier;
 
-        dmg_result.add(weapon_base_dmg_type, str_mod_bonus);
+        dmg_breakdown.add(DamageSource::Strength, weapon_base_dmg_type, str_mod_bonus);
 
         // Weapon base damage
         let weapon_base_dmg = attacker.weapon.base.damage.roll_m(multiplier);
-        dmg_result.add(weapon_base_dmg_type, weapon_base_dmg);
+        dmg_breakdown.add(
+            DamageSource::WeaponBase,
+            weapon_base_dmg_type,
+            weapon_base_dmg,
+        );
 
         // Weapon damage bonuses
-        let _ = attacker
-            .weapon
-            .item_properties
-            .iter()
-            .filter(|x| match x {
-                ItemProperty::EnchantmentBonus(_) => true,
-                ItemProperty::DamageBonus(_) => true,
-                ItemProperty::MassiveCrit(_) => {
-                    if is_crit {
-                        true
-                    } else {
-                        false
-                    }
-                }
-                _ => false,
-            })
-            .map(|x| match x {
+        let mut dmg_bonus_index = 0;
+
+        for item_property in &attacker.weapon.item_properties {
+            match item_property {
                 ItemProperty::EnchantmentBonus(bonus) => {
-                    dmg_result.add(weapon_base_dmg_type, bonus * multiplier);
+                    dmg_breakdown.add(
+                        DamageSource::Enchantment,
+                        weapon_base_dmg_type,
+                        bonus * multiplier,
+                    );
                 }
                 ItemProperty::DamageBonus(dmg) => {
-                    dmg_result.add(dmg.type_, dmg.roll_m(multiplier));
-                }
-                ItemProperty::MassiveCrit(dice) => {
-                    dmg_result.add(weapon_base_dmg_type, dice.roll());
+                    dmg_breakdown.add(
+                        DamageSource::DamageBonus(dmg_bonus_index),
+                        dmg.type_,
+                        dmg.roll_m(multiplier),
+                    );
+                    dmg_bonus_index += 1;
                 }
                 _ => (),
-            })
-            .collect::<Vec<_>>();
+            }
+        }
+
+        // Feat damage
+        let feat_effects = &hand.feat_effects;
 
-        // Bane of Enemies
-        if attacker.has_bane_of_enemies() {
-            dmg_result.add(weapon_base_dmg_type, Dice::from("2d6").roll_m(multiplier));
+        for definition in feat_effects {
+            if let Some(dice) = definition.effect.hit_damage(attacker, defender) {
+                dmg_breakdown.add(
+                    DamageSource::Feat(definition.name.clone()),
+                    weapon_base_dmg_type,
+                    dice.roll_m(multiplier),
+                );
+            }
         }
 
-        // Overwhelming Critical
-        if attacker.has_overwhelming_critical() {
-            dmg_result.add(weapon_base_dmg_type, Dice::from("1d6").roll_m(multiplier));
+        // Favored enemy
+        let favored_enemy_dmg = attacker.favored_enemy_dmg(defender);
+
+        if favored_enemy_dmg > 0 {
+            dmg_breakdown.add(
+                DamageSource::FavoredEnemy,
+                weapon_base_dmg_type,
+                favored_enemy_dmg * multiplier,
+            );
         }
 
-        // Weapon Specialization
-        if attacker.has_weapon_spec() {
-            dmg_result.add(weapon_base_dmg_type, 2 * multiplier);
+        // Activated abilities
+        for ability in abilities.iter().filter(|x| x.dmg > 0) {
+            dmg_breakdown.add(
+                DamageSource::Ability(ability.name.clone()),
+                ability.dmg_type.unwrap_or(weapon_base_dmg_type),
+                ability.dmg * multiplier,
+            );
         }
 
-        // Epic Weapon Specialization
-        if attacker.has_epic_weapon_spec() {
-            dmg_result.add(weapon_base_dmg_type, 4 * multiplier);
+        let multiplied_dmg_breakdown = if is_crit_prevented {
+            dmg_breakdown.clone()
+        } else {
+            DamageBreakdown::new()
+        };
+
+        self.damage_hooks
+            .run(DamageStage::PostMultiplier, &context, &mut dmg_breakdown);
+
+        // Damage only critical hits add, not multiplied
+        let mut crit_dmg_breakdown = DamageBreakdown::new();
+
+        if is_crit || is_crit_prevented {
+            for item_property in &attacker.weapon.item_properties {
+                if let ItemProperty::MassiveCrit(dice) = item_property {
+                    crit_dmg_breakdown.add(
+                        DamageSource::MassiveCrit,
+                        weapon_base_dmg_type,
+                        dice.roll(),
+                    );
+                }
+            }
+
+            for definition in feat_effects {
+                if let Some(dice) = definition.effect.crit_damage(attacker, defender) {
+                    crit_dmg_breakdown.add(
+                        DamageSource::Feat(definition.name.clone()),
+                        weapon_base_dmg_type,
+                        dice.roll(),
+                    );
+                }
+            }
         }
 
-        // Apply damage immunity and reduction
-        let dmg_types = dmg_result.get_types();
+        if is_crit {
+            for entry in crit_dmg_breakdown.entries() {
+                dmg_breakdown.add(entry.source.clone(), entry.type_, entry.amount);
+            }
+        }
 
-        for dmg_type in dmg_types {
-            let defender_dmg_immunity = defender.damage_immunity(dmg_type);
-            let defender_dmg_reduction = defender.damage_reduction(dmg_type);
+        // Sneak and death attack, not multiplied
+        if is_sneak_attack {
+            for (source, dice) in [
+                (DamageSource::SneakAttack, attacker.sneak_attack_dice),
+                (DamageSource::DeathAttack, attacker.death_attack_dice),
+            ] {
+                if dice > 0 {
+                    dmg_breakdown.add(source, weapon_base_dmg_type, Dice::new(dice, 6).roll());
+                }
+            }
+        }
 
-            if defender_dmg_immunity > 0 {
-                dmg_result.sub(
-                    dmg_type,
-                    dmg_result.get(dmg_type) * defender_dmg_immunity / 100,
+        self.damage_hooks
+            .run(DamageStage::PreMitigation, &context, &mut dmg_breakdown);
+
+        // The critical hit as it would have been, mitigated the same way,
+        // before the hooks after mitigation add the same to both.
+        let crit_dmg = is_crit_prevented.then(|| {
+            let mut crit_breakdown = dmg_breakdown.clone();
+
+            for entry in multiplied_dmg_breakdown.entries() {
+                crit_breakdown.add(
+                    entry.source.clone(),
+                    entry.type_,
+                    entry.amount * (crit_multiplier - 1),
                 );
             }
 
-            if defender_dmg_reduction > 0 {
-                dmg_result.sub(dmg_type, defender_dmg_reduction);
+            for entry in crit_dmg_breakdown.entries() {
+                crit_breakdown.add(entry.source.clone(), entry.type_, entry.amount);
             }
+
+            Self::apply_mitigation(defender, &mut crit_breakdown);
+
+            crit_breakdown.to_damage_result().total_dmg()
+        });
+
+        Self::apply_mitigation(defender, &mut dmg_breakdown);
+
+        let crit_immunity_prevented =
+            crit_dmg.map(|x| x - dmg_breakdown.to_damage_result().total_dmg());
+
+        self.damage_hooks
+            .run(DamageStage::PostMitigation, &context, &mut dmg_breakdown);
+
+        HitDamage {
+            dmg_breakdown,
+            crit_immunity_prevented,
         }
+    }
 
-        dmg_result
+    // Attacker wielding the weapon of the hand the attack is made with.
+    fn attacker_for(&self, type_: AttackType) -> &Character {
+        match (&self.off_hand_attacker, type_) {
+            (Some(off_hand_attacker), AttackType::OffHand) => off_hand_attacker,
+            _ => self.attacker,
+        }
     }
 
-    pub fn resolve_round(&self) -> CombatStatistics {
-        let mut round_statistics = CombatStatistics::default();
-        let mut defender_can_epic_dodge = true;
+    fn hand_for(&self, type_: AttackType) -> &HandProfile {
+        match (&self.off_hand, type_) {
+            (Some(off_hand), AttackType::OffHand) => off_hand,
+            _ => &self.main_hand,
+        }
+    }
 
-        for atk_no in 1..=self.attacker.total_apr() {
-            let atk_info = if let Some(atk_info) = self.attacker.atk_ab(atk_no) {
-                atk_info
-            } else {
-                println!("Combat::round() - Attack info is none!");
-                continue;
-            };
+    // AB of the attack against the defender, with the given abilities used
+    // on it.
+    pub(crate) fn attack_ab(&self, atk_info: &AttackInfo, abilities: &[&ActivatedAbility]) -> i32 {
+        atk_info.ab
+            + self.hand_for(atk_info.type_).ab_against
+            + abilities.iter().map(|x| x.ab).sum::<i32>()
+    }
 
-            let defender_concealment = Self::resolve_concealment(self.attacker, self.defender);
+    fn resolve_attack(
+        &self,
+        atk_info: &AttackInfo,
+        state: &mut RoundState,
+        abilities: &[&ActivatedAbility],
+    ) -> AttackResult {
+        let hand = self.hand_for(atk_info.type_);
+        let defender_concealment = hand.concealment;
+
+        // Concealment check
+        if defender_concealment > 0.0 && (Dice::from("1d100").roll() as f32) < defender_concealment
+        {
+            return AttackResult::new(HitResult::TargetConcealed);
+        }
 
-            // Concealment check
-            if defender_concealment > 0.0
-                && (Dice::from("1d100").roll() as f32) < defender_concealment
-            {
-                round_statistics.concealed_attacks += 1;
-                round_statistics.total_misses += 1;
+        let ab = self.attack_ab(atk_info, abilities);
+        let hit_roll = Dice::from("1d20").roll();
 
-                continue;
-            }
+        if hit_roll == 1 || (hit_roll != 20 && ab + hit_roll < self.defender_ac) {
+            return AttackResult::new(HitResult::Miss);
+        }
 
-            let hit_roll = Dice::from("1d20").roll();
+        if self.defender_dodges_first_hit && state.defender_can_epic_dodge {
+            state.defender_can_epic_dodge = false;
 
-            if hit_roll != 1 && (hit_roll == 20 || (atk_info.ab + hit_roll >= self.defender.ac)) {
-                if self.defender.has_epic_dodge() && defender_can_epic_dodge {
-                    defender_can_epic_dodge = false;
+            return AttackResult::new(HitResult::EpicDodged);
+        }
 
-                    round_statistics.epic_dodged_attacks += 1;
-                    round_statistics.total_misses += 1;
+        // Critical check
+        let is_threat = hit_roll >= hand.threat_range;
+        let is_confirmed = is_threat && ab + Dice::from("1d20").roll() >= self.defender_ac;
+        let is_crit = is_confirmed && !self.defender_crit_immune;
 
-                    continue;
-                }
+        let mut attack_result = AttackResult::new(if is_crit {
+            HitResult::CriticalHit
+        } else {
+            HitResult::Hit
+        });
+        attack_result.is_threat = is_threat;
+
+        // Calculate damage
+        let hit_damage =
+            self.resolve_damage_breakdown(*atk_info, is_crit, is_confirmed && !is_crit, abilities);
+        attack_result.dmg_breakdown = hit_damage.dmg_breakdown;
+        attack_result.crit_immunity_prevented = hit_damage.crit_immunity_prevented;
 
-                // Critical check
-                let is_crit = if !self.defender.is_crit_immune()
-                    && hit_roll >= self.attacker.weapon_threat_range()
-                    && atk_info.ab + Dice::from("1d20").roll() >= self.defender.ac
-                {
-                    round_statistics.critical_hits += 1;
-                    true
-                } else {
-                    false
-                };
+        attack_result
+    }
+
+    // Resolves an independent round. Activated abilities aren't used, as
+    // their uses are spent over a fight.
+    pub fn resolve_round(&self) -> CombatStatistics {
+        self.resolve_fight_round(&mut RoundState::default(), &mut AbilityState::default())
+    }
 
-                round_statistics.total_hits += 1;
+    // Resolves the next round of a fight, with the attacker's activated
+    // abilities used as their state allows.
+    pub(crate) fn resolve_fight_round(
+        &self,
+        state: &mut RoundState,
+        abilities: &mut AbilityState,
+    ) -> CombatStatistics {
+        abilities.next_round();
 
-                // Calculate damage
-                let dmg_result =
-                    Self::resolve_damage(self.attacker, self.defender, atk_info, is_crit);
+        let mut round_statistics = CombatStatistics::default();
 
-                round_statistics.dmg_dealt.add_from(&dmg_result);
+        for atk_no in 1..=self.attacker.total_apr() {
+            let atk_info = if let Some(atk_info) = self.attacker.atk_ab(atk_no) {
+                atk_info
             } else {
-                round_statistics.total_misses += 1;
-            }
+                println!("Combat::round() - Attack info is none!");
+                continue;
+            };
+
+            let attack_abilities = abilities.attack_abilities(atk_no);
+            let attack_result = self.resolve_attack(&atk_info, state, &attack_abilities);
+
+            round_statistics.add_attack(atk_no, &atk_info, &attack_result);
         }
 
+        round_statistics
+            .dmg_per_round
+            .add(round_statistics.dmg_dealt.total_dmg() as f64);
+
         round_statistics
     }
 }
@@ -328,10 +1000,16 @@ impl<'a> Combat<'a> {
 mod test {
     use crate::{
         character::{AbilityList, Character, CharacterBuilder},
-        combat::{AttackInfo, AttackType, Combat},
+        class::{Class, ClassLevels},
+        combat::{AttackContext, AttackInfo, AttackType, Combat, CombatStatistics},
+        damage_source::DamageSource,
         dice::Dice,
         feat::feat_db::get_feat,
-        item::{Damage, DamageResult, DamageType, ItemProperty, Weapon, WeaponBase},
+        item::{
+            weapon_db::get_weapon_base, Damage, DamageResult, DamageType, ItemProperty, Weapon,
+            WeaponBase,
+        },
+        race::RacialType,
         size::SizeCategory,
     };
 
@@ -485,23 +1163,19 @@ mod test {
             .physical_damage_reduction(5)
             .build();
 
-        let round_result = Combat::resolve_damage(
-            &attacker,
-            &defender,
-            AttackInfo::new(50, AttackType::MainHand),
-            false,
-        );
+        let round_result = Combat::new(&attacker, &defender)
+            .resolve_damage_breakdown(AttackInfo::new(50, AttackType::MainHand), false, false, &[])
+            .dmg_breakdown
+            .to_damage_result();
 
         assert_eq!(round_result.get(DamageType::Slashing), 17);
         assert_eq!(round_result.get(DamageType::Divine), 4);
         assert_eq!(round_result.total_dmg(), 21);
 
-        let round_result = Combat::resolve_damage(
-            &attacker,
-            &defender,
-            AttackInfo::new(50, AttackType::MainHand),
-            true,
-        );
+        let round_result = Combat::new(&attacker, &defender)
+            .resolve_damage_breakdown(AttackInfo::new(50, AttackType::MainHand), true, false, &[])
+            .dmg_breakdown
+            .to_damage_result();
 
         assert_eq!(round_result.get(DamageType::Slashing), 66);
         assert_eq!(round_result.get(DamageType::Divine), 12);
@@ -540,22 +1214,18 @@ mod test {
             .physical_damage_reduction(0)
             .build();
 
-        let round_result = Combat::resolve_damage(
-            &attacker,
-            &defender,
-            AttackInfo::new(50, AttackType::MainHand),
-            false,
-        );
+        let round_result = Combat::new(&attacker, &defender)
+            .resolve_damage_breakdown(AttackInfo::new(50, AttackType::MainHand), false, false, &[])
+            .dmg_breakdown
+            .to_damage_result();
 
         assert_eq!(round_result.get(DamageType::Slashing), 31);
 
         // Test offhand damage penalty
-        let round_result = Combat::resolve_damage(
-            &attacker,
-            &defender,
-            AttackInfo::new(50, AttackType::OffHand),
-            false,
-        );
+        let round_result = Combat::new(&attacker, &defender)
+            .resolve_damage_breakdown(AttackInfo::new(50, AttackType::OffHand), false, false, &[])
+            .dmg_breakdown
+            .to_damage_result();
 
         assert_eq!(round_result.get(DamageType::Slashing), 20);
 
@@ -575,4 +1245,237 @@ mod test {
         assert_eq!(dmg1.get(DamageType::Divine), 1);
         assert_eq!(dmg1.total_dmg(), 13);
     }
+
+    #[test]
+    fn attack_slots() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(20)
+            .base_apr(4)
+            .extra_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .feats(vec![get_feat("Dual Wielding")])
+            .build();
+        let defender = Character::builder().ac(30).build();
+        let combat = Combat::new(&attacker, &defender);
+
+        let mut statistics = combat.resolve_round();
+        statistics.add_from(&combat.resolve_round());
+
+        assert_eq!(statistics.attack_slots.len(), 7);
+        assert_eq!(
+            statistics
+                .attack_slots
+                .iter()
+                .map(|x| x.total_attacks)
+                .sum::<i64>(),
+            statistics.total_attacks()
+        );
+        assert_eq!(
+            statistics
+                .attack_slots
+                .iter()
+                .map(|x| x.dmg_dealt)
+                .sum::<i64>(),
+            statistics.dmg_dealt.total_dmg() as i64
+        );
+
+        assert_eq!(
+            statistics
+                .dmg_sources
+                .iter()
+                .map(|x| x.dealt())
+                .sum::<i64>(),
+            statistics.dmg_dealt.total_dmg() as i64
+        );
+        assert!(statistics
+            .dmg_source_statistics(DamageSource::WeaponBase)
+            .is_some());
+
+        let slot = &statistics.attack_slots[3];
+        assert_eq!(slot.atk_no, 4);
+        assert_eq!(slot.ab, 5);
+        assert_eq!(slot.type_, AttackType::MainHand);
+        assert_eq!(slot.total_attacks, 2);
+
+        let main_hand = statistics
+            .attack_type_statistics(AttackType::MainHand)
+            .unwrap();
+        assert_eq!(main_hand.total_attacks, 8);
+        assert_eq!(main_hand.ab, 20);
+
+        let off_hand = statistics
+            .attack_type_statistics(AttackType::OffHand)
+            .unwrap();
+        assert_eq!(off_hand.total_attacks, 4);
+        assert_eq!(off_hand.atk_no, 6);
+
+        let statistics = CombatStatistics::new();
+        assert!(statistics
+            .attack_type_statistics(AttackType::Extra)
+            .is_none());
+    }
+
+    #[test]
+    fn critical_hits() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(100)
+            .base_apr(4)
+            .weapon(Weapon::new("".into(), get_weapon_base("Scimitar"), vec![]))
+            .build();
+        let defender = Character::builder().ac(10).build();
+        let immune_defender = Character::builder()
+            .ac(10)
+            .feats(vec![get_feat("Critical Immunity")])
+            .build();
+
+        let mut statistics = CombatStatistics::new();
+        let mut immune_statistics = CombatStatistics::new();
+
+        for _ in 0..200 {
+            statistics.add_from(&Combat::new(&attacker, &defender).resolve_round());
+            immune_statistics.add_from(&Combat::new(&attacker, &immune_defender).resolve_round());
+        }
+
+        // Every threat confirms against such a low AC.
+        assert!(statistics.critical_threats > 0);
+        assert_eq!(statistics.critical_hits, statistics.critical_threats);
+        assert_eq!(statistics.crit_confirmation_rate(), 1.0);
+        assert!(statistics.threat_rate() > 0.0 && statistics.threat_rate() < 1.0);
+        assert!(statistics.crit_dmg > 0);
+        assert!(statistics.crit_dmg_share() > 0.0 && statistics.crit_dmg_share() < 1.0);
+        assert_eq!(
+            statistics.crit_dmg + statistics.normal_hit_dmg(),
+            statistics.dmg_dealt.total_dmg() as i64
+        );
+        assert_eq!(statistics.crits_prevented, 0);
+
+        assert_eq!(immune_statistics.critical_hits, 0);
+        assert_eq!(immune_statistics.crit_dmg, 0);
+        assert!(immune_statistics.critical_threats > 0);
+        assert_eq!(
+            immune_statistics.crits_prevented,
+            immune_statistics.critical_threats
+        );
+        assert_eq!(immune_statistics.crit_confirmation_rate(), 1.0);
+        assert!(immune_statistics.crit_immunity_dmg_prevented > 0);
+
+        // A x2 critical hit would have doubled the damage rolled for the hit.
+        let hit_damage = Combat::new(&attacker, &immune_defender).resolve_damage_breakdown(
+            AttackInfo::new(100, AttackType::MainHand),
+            false,
+            true,
+            &[],
+        );
+        assert_eq!(
+            hit_damage.crit_immunity_prevented,
+            Some(hit_damage.dmg_breakdown.to_damage_result().total_dmg())
+        );
+        assert!(immune_statistics.to_string().contains("CRITICAL HITS"));
+    }
+
+    #[test]
+    fn sneak_attack() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(100)
+            .base_apr(1)
+            .sneak_attack_dice(5)
+            .death_attack_dice(2)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let defender = Character::builder().ac(10).build();
+        let immune_defender = Character::builder()
+            .ac(10)
+            .feats(vec![get_feat("Sneak Attack Immunity")])
+            .build();
+        let flanked = AttackContext {
+            flanked: true,
+            ..Default::default()
+        };
+        let flat_footed = AttackContext {
+            flat_footed: true,
+            ..Default::default()
+        };
+
+        let mut statistics = CombatStatistics::new();
+        let mut sneak_statistics = CombatStatistics::new();
+        let mut immune_statistics = CombatStatistics::new();
+
+        for _ in 0..100 {
+            statistics.add_from(&Combat::new(&attacker, &defender).resolve_round());
+            sneak_statistics
+                .add_from(&Combat::with_context(&attacker, &defender, flanked).resolve_round());
+            immune_statistics.add_from(
+                &Combat::with_context(&attacker, &immune_defender, flat_footed).resolve_round(),
+            );
+        }
+
+        assert!(statistics
+            .dmg_source_statistics(DamageSource::SneakAttack)
+            .is_none());
+        assert!(immune_statistics
+            .dmg_source_statistics(DamageSource::SneakAttack)
+            .is_none());
+
+        // Rolled once per hit, critical hits included.
+        let sneak_attack = sneak_statistics
+            .dmg_source_statistics(DamageSource::SneakAttack)
+            .unwrap();
+        assert!(sneak_attack.amount >= 5 * sneak_statistics.total_hits);
+        assert!(sneak_attack.amount <= 30 * sneak_statistics.total_hits);
+
+        let death_attack = sneak_statistics
+            .dmg_source_statistics(DamageSource::DeathAttack)
+            .unwrap();
+        assert!(death_attack.amount <= 12 * sneak_statistics.total_hits);
+        assert!(sneak_statistics.to_string().contains("SNEAK ATTACK"));
+    }
+
+    #[test]
+    fn favored_enemy() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .class_levels(ClassLevels(vec![(Class::Ranger, 21)]))
+            .feats(vec![get_feat("Bane of Enemies")])
+            .favored_enemies(vec![RacialType::Orc])
+            .ab(100)
+            .base_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let orc = Character::builder().race(RacialType::Orc).ac(10).build();
+        let human = Character::builder().ac(10).build();
+
+        assert_eq!(attacker.ab_against(&orc), 2);
+        assert_eq!(attacker.ab_against(&human), 0);
+        assert_eq!(attacker.favored_enemy_dmg(&orc), 1 + 4);
+        assert_eq!(attacker.favored_enemy_dmg(&human), 0);
+
+        let mut orc_statistics = CombatStatistics::new();
+        let mut human_statistics = CombatStatistics::new();
+
+        for _ in 0..50 {
+            orc_statistics.add_from(&Combat::new(&attacker, &orc).resolve_round());
+            human_statistics.add_from(&Combat::new(&attacker, &human).resolve_round());
+        }
+
+        // Multiplied on critical hits.
+        assert_eq!(
+            orc_statistics
+                .dmg_source_statistics(DamageSource::FavoredEnemy)
+                .unwrap()
+                .amount,
+            5 * (orc_statistics.total_hits + orc_statistics.critical_hits)
+        );
+        assert!(orc_statistics
+            .dmg_source_statistics(DamageSource::Feat("Bane of Enemies".into()))
+            .is_some());
+        assert!(human_statistics
+            .dmg_source_statistics(DamageSource::FavoredEnemy)
+            .is_none());
+        assert!(human_statistics
+            .dmg_source_statistics(DamageSource::Feat("Bane of Enemies".into()))
+            .is_none());
+    }
 }
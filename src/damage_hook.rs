@@ -0,0 +1,173 @@
+use super::{character::Character, combat::AttackInfo, damage_source::DamageBreakdown};
+use std::sync::{Arc, OnceLock, RwLock};
+
+// Points of `Combat::resolve_damage_breakdown` hooks run at, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DamageStage {
+    // Before any damage is rolled. The hook gets an empty breakdown and runs
+    // once per critical multiplier, so what it adds is multiplied like
+    // weapon damage.
+    PreMultiplier,
+    // After the multiplied damage is rolled, before damage that only
+    // critical hits add.
+    PostMultiplier,
+    // After all damage is rolled, before immunity and reduction.
+    PreMitigation,
+    // After immunity and reduction. Damage added here isn't mitigated.
+    PostMitigation,
+}
+
+pub struct DamageHookContext<'a> {
+    pub attacker: &'a Character,
+    pub defender: &'a Character,
+    pub atk_info: AttackInfo,
+    pub is_crit: bool,
+    pub is_sneak_attack: bool,
+}
+
+// Adds or transforms the damage of a hit, e.g. a custom server effect or a
+// proposed rule change. Implemented for closures taking the same arguments.
+pub trait DamageHook: Send + Sync {
+    fn apply(&self, context: &DamageHookContext, dmg_breakdown: &mut DamageBreakdown);
+}
+
+impl<F> DamageHook for F
+where
+    F: Fn(&DamageHookContext, &mut DamageBreakdown) + Send + Sync,
+{
+    fn apply(&self, context: &DamageHookContext, dmg_breakdown: &mut DamageBreakdown) {
+        self(context, dmg_breakdown)
+    }
+}
+
+type DamageHookRegistry = RwLock<Vec<(DamageStage, Arc<dyn DamageHook>)>>;
+
+static DAMAGE_HOOKS: OnceLock<DamageHookRegistry> = OnceLock::new();
+
+fn registry() -> &'static DamageHookRegistry {
+    DAMAGE_HOOKS.get_or_init(|| RwLock::new(vec![]))
+}
+
+// Registers a hook for every combat created afterwards, on every thread.
+// Hooks of the same stage run in the order they were registered.
+pub fn register_damage_hook(stage: DamageStage, hook: impl DamageHook + 'static) {
+    registry().write().unwrap().push((stage, Arc::new(hook)));
+}
+
+pub fn clear_damage_hooks() {
+    registry().write().unwrap().clear();
+}
+
+// Hooks registered when a combat is created, so hits don't go through the
+// registry's lock.
+#[derive(Clone, Default)]
+pub(crate) struct DamageHooks(Vec<(DamageStage, Arc<dyn DamageHook>)>);
+
+impl DamageHooks {
+    pub fn registered() -> Self {
+        Self(registry().read().unwrap().clone())
+    }
+
+    pub fn run(
+        &self,
+        stage: DamageStage,
+        context: &DamageHookContext,
+        dmg_breakdown: &mut DamageBreakdown,
+    ) {
+        for (_, hook) in self.0.iter().filter(|(x, _)| *x == stage) {
+            hook.apply(context, dmg_breakdown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{register_damage_hook, DamageHookContext, DamageStage};
+    use crate::{
+        character::{AbilityList, Character},
+        damage_source::{DamageBreakdown, DamageSource},
+        item::{weapon_db::get_weapon_base, DamageType, Weapon},
+        simulator::CombatSimulator,
+    };
+
+    // Hooks apply to every combat, so these only act on their own attacker.
+    fn is_hooked(context: &DamageHookContext) -> bool {
+        context.attacker.name == "Hooked"
+    }
+
+    #[test]
+    fn damage_hooks() {
+        register_damage_hook(
+            DamageStage::PreMultiplier,
+            |context: &DamageHookContext, dmg_breakdown: &mut DamageBreakdown| {
+                if is_hooked(context) {
+                    dmg_breakdown.add(DamageSource::Custom("Flat".into()), DamageType::Fire, 3);
+                }
+            },
+        );
+        register_damage_hook(
+            DamageStage::PreMitigation,
+            |context: &DamageHookContext, dmg_breakdown: &mut DamageBreakdown| {
+                if is_hooked(context) {
+                    for entry in dmg_breakdown.entries_mut() {
+                        if entry.source == DamageSource::WeaponBase {
+                            entry.amount = 0;
+                        }
+                    }
+                }
+            },
+        );
+        register_damage_hook(
+            DamageStage::PostMitigation,
+            |context: &DamageHookContext, dmg_breakdown: &mut DamageBreakdown| {
+                if is_hooked(context) && context.is_crit {
+                    dmg_breakdown.add(DamageSource::Custom("True".into()), DamageType::Slashing, 7);
+                }
+            },
+        );
+
+        let attacker = Character::builder()
+            .name("Hooked".into())
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(100)
+            .base_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let defender = Character::builder()
+            .ac(10)
+            .physical_damage_reduction(100)
+            .build();
+        let statistics = CombatSimulator::new(100).begin(&attacker, &defender);
+
+        let flat = statistics
+            .dmg_source_statistics(DamageSource::Custom("Flat".into()))
+            .unwrap();
+        // 3 per hit, 6 per critical hit.
+        assert_eq!(
+            flat.amount,
+            3 * (statistics.total_hits + statistics.critical_hits)
+        );
+        assert_eq!(
+            statistics
+                .dmg_source_statistics(DamageSource::WeaponBase)
+                .unwrap()
+                .amount,
+            0
+        );
+
+        // Only the damage added after mitigation gets through the reduction.
+        assert_eq!(
+            statistics.dmg_dealt.get(DamageType::Slashing) as i64,
+            7 * statistics.critical_hits
+        );
+
+        let attacker = Character {
+            name: "".into(),
+            ..attacker
+        };
+        let statistics = CombatSimulator::new(10).begin(&attacker, &defender);
+        assert!(statistics
+            .dmg_source_statistics(DamageSource::Custom("Flat".into()))
+            .is_none());
+    }
+}
@@ -0,0 +1,216 @@
+use super::item::{DamageResult, DamageType};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageSource {
+    Strength,
+    WeaponBase,
+    Enchantment,
+    // Index of the damage bonus among the weapon's damage bonus properties.
+    DamageBonus(usize),
+    MassiveCrit,
+    SneakAttack,
+    DeathAttack,
+    FavoredEnemy,
+    // Name of the feat the damage comes from.
+    Feat(String),
+    Ability(String),
+    // Damage added by a damage hook, named by the hook.
+    Custom(String),
+}
+
+impl std::fmt::Display for DamageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Strength => write!(f, "Strength"),
+            Self::WeaponBase => write!(f, "Weapon Base"),
+            Self::Enchantment => write!(f, "Enchantment"),
+            Self::DamageBonus(i) => write!(f, "Damage Bonus #{}", i + 1),
+            Self::MassiveCrit => write!(f, "Massive Crit"),
+            Self::SneakAttack => write!(f, "Sneak Attack"),
+            Self::DeathAttack => write!(f, "Death Attack"),
+            Self::FavoredEnemy => write!(f, "Favored Enemy"),
+            Self::Feat(name) | Self::Ability(name) | Self::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DamageEntry {
+    pub source: DamageSource,
+    pub type_: DamageType,
+    // Damage before immunity and reduction are applied.
+    pub amount: i32,
+    pub immunity: i32,
+    pub reduction: i32,
+}
+
+impl DamageEntry {
+    pub fn dealt(&self) -> i32 {
+        self.amount - self.immunity - self.reduction
+    }
+}
+
+// Damage of a single hit, tagged with the source each part of it comes
+// from.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct DamageBreakdown {
+    entries: Vec<DamageEntry>,
+}
+
+impl DamageBreakdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> &Vec<DamageEntry> {
+        &self.entries
+    }
+
+    pub fn entries_mut(&mut self) -> &mut Vec<DamageEntry> {
+        &mut self.entries
+    }
+
+    pub fn add(&mut self, source: DamageSource, type_: DamageType, amount: i32) {
+        self.entries.push(DamageEntry {
+            source,
+            type_,
+            amount,
+            immunity: 0,
+            reduction: 0,
+        });
+    }
+
+    // Spreads the damage a type lost to immunity or reduction over the
+    // entries of that type, proportional to their share of it.
+    pub fn distribute_mitigation(&mut self, type_: DamageType, immunity: i32, reduction: i32) {
+        let indices = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.type_ == type_)
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+
+        if indices.is_empty() {
+            return;
+        }
+
+        let weights = indices
+            .iter()
+            .map(|i| self.entries[*i].amount.max(0) as i64)
+            .collect::<Vec<i64>>();
+        let total_weight = weights.iter().sum::<i64>();
+
+        for (loss, is_immunity) in [(immunity, true), (reduction, false)] {
+            let mut shares = vec![0; indices.len()];
+
+            if total_weight > 0 {
+                let mut remainders = vec![];
+
+                for (j, weight) in weights.iter().enumerate() {
+                    let share = loss as i64 * weight;
+
+                    shares[j] = share.div_euclid(total_weight) as i32;
+                    remainders.push((share.rem_euclid(total_weight), j));
+                }
+
+                // Hand out what integer division left over to the entries
+                // with the largest remainders.
+                remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+                let remainder = (loss - shares.iter().sum::<i32>()) as usize;
+
+                for (_, j) in remainders.iter().take(remainder) {
+                    shares[*j] += 1;
+                }
+            } else {
+                shares[0] = loss;
+            }
+
+            for (i, share) in indices.iter().zip(shares) {
+                if is_immunity {
+                    self.entries[*i].immunity += share;
+                } else {
+                    self.entries[*i].reduction += share;
+                }
+            }
+        }
+    }
+
+    pub fn to_damage_result(&self) -> DamageResult {
+        let dmg_result = DamageResult::new();
+
+        for entry in &self.entries {
+            dmg_result.add(entry.type_, entry.dealt());
+        }
+
+        dmg_result
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DamageSourceStatistics {
+    pub source: DamageSource,
+    pub amount: i64,
+    pub immunity: i64,
+    pub reduction: i64,
+}
+
+impl DamageSourceStatistics {
+    pub fn new(source: DamageSource) -> Self {
+        Self {
+            source,
+            amount: 0,
+            immunity: 0,
+            reduction: 0,
+        }
+    }
+
+    pub fn dealt(&self) -> i64 {
+        self.amount - self.immunity - self.reduction
+    }
+
+    pub fn add_from(&mut self, other: &DamageSourceStatistics) {
+        self.amount += other.amount;
+        self.immunity += other.immunity;
+        self.reduction += other.reduction;
+    }
+
+    pub fn add_entry(&mut self, entry: &DamageEntry) {
+        self.amount += entry.amount as i64;
+        self.immunity += entry.immunity as i64;
+        self.reduction += entry.reduction as i64;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DamageBreakdown, DamageSource};
+    use crate::item::DamageType;
+
+    #[test]
+    fn damage_breakdown() {
+        let mut breakdown = DamageBreakdown::new();
+        breakdown.add(DamageSource::Strength, DamageType::Slashing, 10);
+        breakdown.add(DamageSource::WeaponBase, DamageType::Slashing, 5);
+        breakdown.add(DamageSource::DamageBonus(0), DamageType::Fire, 6);
+        breakdown.add(DamageSource::Enchantment, DamageType::Slashing, 0);
+
+        breakdown.distribute_mitigation(DamageType::Slashing, 1, 5);
+
+        let entries = breakdown.entries();
+        assert_eq!(entries[0].immunity + entries[1].immunity, 1);
+        assert_eq!(entries[0].reduction + entries[1].reduction, 5);
+        assert_eq!(entries[0].reduction, 3);
+        assert_eq!(entries[1].reduction, 2);
+        assert_eq!(entries[2].dealt(), 6);
+        assert_eq!(entries[3].dealt(), 0);
+
+        let dmg_result = breakdown.to_damage_result();
+        assert_eq!(dmg_result.get(DamageType::Slashing), 9);
+        assert_eq!(dmg_result.get(DamageType::Fire), 6);
+
+        assert_eq!(DamageSource::DamageBonus(1).to_string(), "Damage Bonus #2");
+    }
+}
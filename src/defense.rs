@@ -0,0 +1,242 @@
+use super::{
+    character::{Character, CharacterBuilder},
+    combat::AttackContext,
+    feat::feat_db::get_feat,
+    string::align_string,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::min;
+
+// A single improvement to a defender whose effect on incoming damage is
+// measured in isolation.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DefenderImprovement {
+    ArmorClass(i32),
+    Concealment(i32),
+    PhysicalImmunity(i32),
+    PhysicalDamageReduction(i32),
+    EpicDodge,
+}
+
+impl DefenderImprovement {
+    // +1 AC, +5% concealment, +5% physical immunity, +1 physical damage
+    // reduction and Epic Dodge.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::ArmorClass(1),
+            Self::Concealment(5),
+            Self::PhysicalImmunity(5),
+            Self::PhysicalDamageReduction(1),
+            Self::EpicDodge,
+        ]
+    }
+
+    pub fn apply(&self, defender: &Character) -> Character {
+        let mut defender = defender.clone();
+
+        match self {
+            Self::ArmorClass(n) => {
+                let ac = defender.effective_ac(&AttackContext::default()) + n;
+
+                defender = CharacterBuilder::from(defender).ac(ac).build();
+            }
+            Self::Concealment(n) => defender.concealment = min(100, defender.concealment + n),
+            Self::PhysicalImmunity(n) => {
+                defender.physical_immunity = min(100, defender.physical_immunity + n)
+            }
+            Self::PhysicalDamageReduction(n) => defender.physical_dmg_reduction += n,
+            Self::EpicDodge => {
+                if !defender.has_epic_dodge() {
+                    defender = CharacterBuilder::from(defender)
+                        .add_feat(get_feat("Epic Dodge"))
+                        .build();
+                }
+            }
+        }
+
+        defender
+    }
+}
+
+impl std::fmt::Display for DefenderImprovement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ArmorClass(n) => write!(f, "{:+} AC", n),
+            Self::Concealment(n) => write!(f, "{:+}% CONCEALMENT", n),
+            Self::PhysicalImmunity(n) => write!(f, "{:+}% PHYSICAL IMMUNITY", n),
+            Self::PhysicalDamageReduction(n) => write!(f, "{:+} PHYSICAL DR", n),
+            Self::EpicDodge => write!(f, "EPIC DODGE"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct DefenseEntry {
+    pub improvement: DefenderImprovement,
+    // Damage taken per round, averaged over the attacker pool.
+    pub dmg_taken_per_round: f64,
+    // How much less damage is taken per round than without the improvement.
+    pub dmg_prevented_per_round: f64,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct DefenseResult {
+    // Damage taken per round from each attacker of the pool, in order.
+    attackers: Vec<(String, f64)>,
+    entries: Vec<DefenseEntry>,
+}
+
+impl DefenseResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_attacker(&mut self, name: String, dmg_taken_per_round: f64) {
+        self.attackers.push((name, dmg_taken_per_round));
+    }
+
+    pub fn add(&mut self, improvement: DefenderImprovement, dmg_taken_per_round: f64) {
+        self.entries.push(DefenseEntry {
+            improvement,
+            dmg_taken_per_round,
+            dmg_prevented_per_round: self.dmg_taken_per_round() - dmg_taken_per_round,
+        });
+    }
+
+    pub fn attackers(&self) -> &Vec<(String, f64)> {
+        &self.attackers
+    }
+
+    pub fn entries(&self) -> &Vec<DefenseEntry> {
+        &self.entries
+    }
+
+    // Damage taken per round without any improvement, averaged over the
+    // attacker pool.
+    pub fn dmg_taken_per_round(&self) -> f64 {
+        if self.attackers.is_empty() {
+            return 0.0;
+        }
+
+        self.attackers.iter().map(|x| x.1).sum::<f64>() / self.attackers.len() as f64
+    }
+
+    // Entries ordered from the most to the least damage prevented.
+    pub fn ranked(&self) -> Vec<&DefenseEntry> {
+        let mut entries = self.entries.iter().collect::<Vec<&DefenseEntry>>();
+
+        entries.sort_by(|a, b| {
+            b.dmg_prevented_per_round
+                .total_cmp(&a.dmg_prevented_per_round)
+        });
+
+        entries
+    }
+}
+
+impl std::fmt::Display for DefenseResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string_list = vec![
+            align_string(
+                "DAMAGE TAKEN PER ROUND",
+                format!("{:.2}", self.dmg_taken_per_round()),
+            ),
+            "".into(),
+            "ATTACKERS".into(),
+        ];
+
+        for (i, (name, dmg_taken_per_round)) in self.attackers.iter().enumerate() {
+            string_list.push(align_string(
+                &format!("    * #{} {}", i + 1, name),
+                format!("{:.2}", dmg_taken_per_round),
+            ));
+        }
+
+        if !self.entries.is_empty() {
+            string_list.push("".into());
+            string_list.push("IMPROVEMENTS".into());
+
+            for entry in self.ranked() {
+                string_list.push(align_string(
+                    &format!("    * {}", entry.improvement),
+                    format!(
+                        "{:.2} ({:+.2})",
+                        entry.dmg_taken_per_round, -entry.dmg_prevented_per_round
+                    ),
+                ));
+            }
+        }
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DefenderImprovement;
+    use crate::{
+        armor_class::{AcType, ArmorClass},
+        character::{AbilityList, Character},
+        combat::AttackContext,
+        item::{weapon_db::get_weapon_base, Weapon},
+        simulator::CombatSimulator,
+    };
+
+    #[test]
+    fn defense() {
+        let defender = Character::builder()
+            .ac(35)
+            .concealment(98)
+            .physical_immunity(98)
+            .build();
+
+        let armored = Character::builder()
+            .armor_class(ArmorClass::new().modifier(AcType::Natural, 10))
+            .build();
+        let improved = DefenderImprovement::ArmorClass(2).apply(&armored);
+        assert_eq!(
+            improved.effective_ac(&AttackContext::default()),
+            armored.effective_ac(&AttackContext::default()) + 2
+        );
+
+        let improved = DefenderImprovement::Concealment(5).apply(&defender);
+        assert_eq!(improved.concealment, 100);
+        let improved = DefenderImprovement::PhysicalImmunity(5).apply(&defender);
+        assert_eq!(improved.physical_immunity, 100);
+        let improved = DefenderImprovement::EpicDodge.apply(&defender);
+        assert!(improved.has_epic_dodge());
+        let improved = DefenderImprovement::EpicDodge.apply(&improved);
+        assert_eq!(improved.feats.len(), 1);
+
+        let defender = Character::builder().ac(35).build();
+        let attackers = [20, 40]
+            .map(|ab| {
+                Character::builder()
+                    .name(format!("AB {}", ab))
+                    .abilities(AbilityList::builder().str(20).build())
+                    .ab(ab)
+                    .base_apr(2)
+                    .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+                    .build()
+            })
+            .to_vec();
+        let simulator = CombatSimulator::new(500);
+
+        let result = simulator.defense(&defender, &attackers, &DefenderImprovement::defaults());
+        assert_eq!(result.attackers().len(), 2);
+        assert!(result.attackers()[0].1 < result.attackers()[1].1);
+        assert!(
+            (result.dmg_taken_per_round()
+                - (result.attackers()[0].1 + result.attackers()[1].1) / 2.0)
+                .abs()
+                < 1e-9
+        );
+        assert_eq!(result.entries().len(), 5);
+        // Immunity and reduction don't change what is rolled, so with the
+        // same rolls they can't do worse.
+        assert!(result.entries()[2].dmg_prevented_per_round >= 0.0);
+        assert!(result.entries()[3].dmg_prevented_per_round > 0.0);
+        assert!(result.entries()[4].dmg_prevented_per_round > 0.0);
+        assert!(result.to_string().contains("EPIC DODGE"));
+    }
+}
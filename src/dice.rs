@@ -1,5 +1,31 @@
-use rand::{thread_rng, Rng};
-use serde::{Serialize, Deserialize};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+// Reseeds the random number generator of the current thread. Every roll
+// made afterwards on this thread is reproducible, which lets two builds be
+// compared with the same sequence of rolls.
+pub fn set_seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+pub(crate) fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+// Runs `f` and then puts the random number generator back in the state it
+// was in before, so reseeding inside `f` doesn't leak into later rolls.
+pub(crate) fn with_saved_rng<T>(f: impl FnOnce() -> T) -> T {
+    let saved_rng = with_rng(|rng| rng.clone());
+    let result = f();
+
+    RNG.with(|rng| *rng.borrow_mut() = saved_rng);
+    result
+}
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Dice {
@@ -19,8 +45,12 @@ impl Dice {
             return 0;
         }
 
+        if self.faces == 1 {
+            return self.rolls;
+        }
+
         for _ in 0..self.rolls {
-            res += thread_rng().gen_range(1..=self.faces);
+            res += with_rng(|rng| rng.gen_range(1..=self.faces));
         }
 
         res
@@ -41,9 +71,9 @@ impl Dice {
     }
 }
 
-impl ToString for Dice {
-    fn to_string(&self) -> String {
-        format!("{}d{}", self.rolls, self.faces)
+impl std::fmt::Display for Dice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}d{}", self.rolls, self.faces)
     }
 }
 
@@ -83,7 +113,7 @@ impl From<String> for Dice {
 
 #[cfg(test)]
 mod test {
-    use crate::dice::Dice;
+    use crate::dice::{set_seed, Dice};
 
     #[test]
     fn dice() {
@@ -138,5 +168,12 @@ mod test {
         assert_eq!(dice.faces, 1);
         assert_eq!(dice.roll(), 0);
         assert_eq!(dice.to_string(), "0d1");
+
+        let dice = Dice::from("10d20");
+        set_seed(42);
+        let first = (0..10).map(|_| dice.roll()).collect::<Vec<i32>>();
+        set_seed(42);
+        let second = (0..10).map(|_| dice.roll()).collect::<Vec<i32>>();
+        assert_eq!(first, second);
     }
 }
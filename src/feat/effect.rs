@@ -0,0 +1,210 @@
+use crate::{character::Character, dice::Dice, item::get_keen_increase};
+
+// What a feat does in combat. Every hook defaults to no effect, so a feat
+// only implements the ones it changes. Attacker hooks are called for the
+// character wielding the weapon the attack is made with.
+pub trait FeatEffect: Send + Sync {
+    // AB added to the attacks of the character.
+    fn attack_bonus(&self, _character: &Character) -> i32 {
+        0
+    }
+
+    // AB added to attacks against the defender, for bonuses that depend on
+    // the target.
+    fn attack_bonus_against(&self, _attacker: &Character, _defender: &Character) -> i32 {
+        0
+    }
+
+    // How much the threat range of the wielded weapon is widened.
+    fn threat_range(&self, _character: &Character) -> i32 {
+        0
+    }
+
+    fn crit_multiplier(&self, _character: &Character) -> i32 {
+        0
+    }
+
+    // Damage of the weapon's base type added to every hit, multiplied on
+    // critical hits.
+    fn hit_damage(&self, _attacker: &Character, _defender: &Character) -> Option<Dice> {
+        None
+    }
+
+    // Damage of the weapon's base type added to critical hits only.
+    fn crit_damage(&self, _attacker: &Character, _defender: &Character) -> Option<Dice> {
+        None
+    }
+
+    // Concealment of the defender, in percent, as it applies to the
+    // attacker.
+    fn concealment(&self, concealment: f32) -> f32 {
+        concealment
+    }
+
+    fn is_crit_immune(&self) -> bool {
+        false
+    }
+
+    fn is_sneak_attack_immune(&self) -> bool {
+        false
+    }
+
+    // Whether the first attack that would hit the defender each round is
+    // dodged instead.
+    fn dodges_first_hit(&self) -> bool {
+        false
+    }
+}
+
+// Feat effect made of fixed modifiers, enough for most feats.
+#[derive(Clone, Default)]
+pub struct FeatModifiers {
+    pub attack_bonus: i32,
+    pub threat_range: i32,
+    pub crit_multiplier: i32,
+    pub hit_damage: Option<Dice>,
+    pub crit_damage: Option<Dice>,
+    pub crit_immune: bool,
+    pub sneak_attack_immune: bool,
+    pub dodges_first_hit: bool,
+}
+
+impl FeatEffect for FeatModifiers {
+    fn attack_bonus(&self, _character: &Character) -> i32 {
+        self.attack_bonus
+    }
+
+    fn threat_range(&self, _character: &Character) -> i32 {
+        self.threat_range
+    }
+
+    fn crit_multiplier(&self, _character: &Character) -> i32 {
+        self.crit_multiplier
+    }
+
+    fn hit_damage(&self, _attacker: &Character, _defender: &Character) -> Option<Dice> {
+        self.hit_damage.clone()
+    }
+
+    fn crit_damage(&self, _attacker: &Character, _defender: &Character) -> Option<Dice> {
+        self.crit_damage.clone()
+    }
+
+    fn is_crit_immune(&self) -> bool {
+        self.crit_immune
+    }
+
+    fn is_sneak_attack_immune(&self) -> bool {
+        self.sneak_attack_immune
+    }
+
+    fn dodges_first_hit(&self) -> bool {
+        self.dodges_first_hit
+    }
+}
+
+// Doubles the base threat range of the wielded weapon.
+pub struct ImprovedCritical;
+
+impl FeatEffect for ImprovedCritical {
+    fn threat_range(&self, character: &Character) -> i32 {
+        get_keen_increase(character.weapon.base.threat_range)
+    }
+}
+
+// +2 AB and 2d6 damage against favored enemies.
+pub struct BaneOfEnemies;
+
+impl FeatEffect for BaneOfEnemies {
+    fn attack_bonus_against(&self, attacker: &Character, defender: &Character) -> i32 {
+        if attacker.is_favored_enemy(defender) {
+            2
+        } else {
+            0
+        }
+    }
+
+    fn hit_damage(&self, attacker: &Character, defender: &Character) -> Option<Dice> {
+        attacker
+            .is_favored_enemy(defender)
+            .then(|| Dice::from("2d6"))
+    }
+}
+
+// Concealment only applies when both concealment rolls fail.
+pub struct BlindFight;
+
+impl FeatEffect for BlindFight {
+    fn concealment(&self, concealment: f32) -> f32 {
+        concealment.powi(2) / 100.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FeatEffect, FeatModifiers};
+    use crate::{
+        attack_bonus::AttackBonusBreakdown,
+        character::{AbilityList, Character},
+        combat::AttackContext,
+        damage_source::DamageSource,
+        dice::Dice,
+        feat::{
+            feat_db::{get_feat, get_weapon_feat, register_feat},
+            FeatDefinition,
+        },
+        item::{weapon_db::get_weapon_base, Weapon},
+        simulator::CombatSimulator,
+    };
+
+    struct Executioner;
+
+    impl FeatEffect for Executioner {
+        fn threat_range(&self, _character: &Character) -> i32 {
+            1
+        }
+
+        fn crit_damage(&self, _attacker: &Character, defender: &Character) -> Option<Dice> {
+            (defender.effective_ac(&AttackContext::default()) < 50).then(|| Dice::from(10))
+        }
+    }
+
+    #[test]
+    fn feat_effects() {
+        register_feat(FeatDefinition::new("Executioner").effect(Executioner));
+        register_feat(
+            FeatDefinition::new("Mighty Blows")
+                .weapon_feat()
+                .effect(FeatModifiers {
+                    attack_bonus: 3,
+                    crit_multiplier: 2,
+                    ..Default::default()
+                }),
+        );
+
+        let character = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(50)
+            .base_apr(1)
+            .feats(vec![
+                get_feat("Executioner"),
+                get_feat("Ki Critical"),
+                get_weapon_feat("Mighty Blows", "Longsword"),
+                get_weapon_feat("Mighty Blows", "Dagger"),
+            ])
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+
+        assert_eq!(character.feat_effects().len(), 3);
+        assert_eq!(character.weapon_threat_range(), 19 - 1 - 2);
+        assert_eq!(character.weapon_crit_multiplier(), 4);
+        assert_eq!(AttackBonusBreakdown::new(&character, vec![]).feats, 3);
+
+        let defender = Character::builder().ac(10).build();
+        let statistics = CombatSimulator::new(100).begin(&character, &defender);
+        let executioner = statistics
+            .dmg_source_statistics(DamageSource::Feat("Executioner".into()))
+            .unwrap();
+        assert!(executioner.amount > 0);
+    }
+}
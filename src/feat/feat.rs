@@ -1,4 +1,64 @@
-use serde::{Serialize, Deserialize};
+use super::effect::{FeatEffect, FeatModifiers};
+use crate::{character::Ability, class::Class};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeatPrerequisite {
+    // Another feat, taken for the same weapon base if both are weapon feats.
+    Feat(&'static str),
+    Ability(Ability, i32),
+    BaseAttackBonus(i32),
+    CharacterLevel(i32),
+    ClassLevel(Class, i32),
+}
+
+impl std::fmt::Display for FeatPrerequisite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Feat(name) => write!(f, "{}", name),
+            Self::Ability(ability, value) => write!(f, "{} {}", ability, value),
+            Self::BaseAttackBonus(value) => write!(f, "BAB {}", value),
+            Self::CharacterLevel(level) => write!(f, "level {}", level),
+            Self::ClassLevel(class, level) => write!(f, "{} {}", level, class),
+        }
+    }
+}
+
+// A feat of the registry, with the prerequisites to take it and what it
+// does in combat.
+pub struct FeatDefinition {
+    pub name: String,
+    pub prerequisites: Vec<FeatPrerequisite>,
+    // Whether the feat can be taken for a single weapon base.
+    pub weapon_feat: bool,
+    pub effect: Box<dyn FeatEffect>,
+}
+
+impl FeatDefinition {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            prerequisites: vec![],
+            weapon_feat: false,
+            effect: Box::new(FeatModifiers::default()),
+        }
+    }
+
+    pub fn prerequisites(mut self, prerequisites: Vec<FeatPrerequisite>) -> Self {
+        self.prerequisites = prerequisites;
+        self
+    }
+
+    pub fn weapon_feat(mut self) -> Self {
+        self.weapon_feat = true;
+        self
+    }
+
+    pub fn effect(mut self, effect: impl FeatEffect + 'static) -> Self {
+        self.effect = Box::new(effect);
+        self
+    }
+}
 
 #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct Feat(pub String);
@@ -7,6 +67,27 @@ impl Feat {
     fn name(&self) -> &String {
         &self.0
     }
+
+    // Feat taken for a single weapon base, e.g.
+    // "Weapon Specialization (Scimitar)".
+    pub fn for_weapon(name: &str, weapon_base: &str) -> Self {
+        Feat(format!("{} ({})", name, weapon_base))
+    }
+
+    // Name of the feat without the weapon base it was taken for.
+    pub fn base_name(&self) -> &str {
+        match self.0.split_once(" (") {
+            Some((name, _)) if self.0.ends_with(')') => name,
+            _ => &self.0,
+        }
+    }
+
+    pub fn weapon_base(&self) -> Option<&str> {
+        match self.0.split_once(" (") {
+            Some((_, weapon_base)) => weapon_base.strip_suffix(')'),
+            None => None,
+        }
+    }
 }
 
 impl std::fmt::Display for Feat {
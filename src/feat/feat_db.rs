@@ -1,31 +1,172 @@
-use super::Feat;
-use std::collections::HashMap;
-
-const FEAT_LIST: &'static [&'static str] = &[
-    "Critical Immunity",
-    "Overwhelming Critical",
-    "Blind Fight",
-    "Bane of Enemies",
-    "Dual Wielding",
-    "Epic Dodge",
-    "Increased Multiplier",
-    "Improved Critical",
-    "Ki Critical",
-    "Weapon Specialization",
-    "Epic Weapon Specialization",
-    "Monk",
-];
-
-pub fn get_feat_list() -> HashMap<&'static str, Feat> {
-    let mut hashmap = HashMap::new();
-
-    for feat_str in FEAT_LIST {
-        hashmap.insert(*feat_str, Feat(feat_str.to_string()));
-    }
-
-    hashmap
+use super::{
+    effect::{BaneOfEnemies, BlindFight, FeatModifiers, ImprovedCritical},
+    Feat, FeatDefinition,
+    FeatPrerequisite::{self, BaseAttackBonus, CharacterLevel, ClassLevel},
+};
+use crate::{character::Ability, class::Class, dice::Dice};
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock, RwLock},
+};
+
+// Feats the registry starts with. Feats without an effect are read directly
+// by the code they change, e.g. Dual Wielding by the attack sequence.
+fn feat_list() -> Vec<FeatDefinition> {
+    vec![
+        FeatDefinition::new("Critical Immunity").effect(FeatModifiers {
+            crit_immune: true,
+            ..Default::default()
+        }),
+        FeatDefinition::new("Sneak Attack Immunity").effect(FeatModifiers {
+            sneak_attack_immune: true,
+            ..Default::default()
+        }),
+        FeatDefinition::new("Overwhelming Critical")
+            .weapon_feat()
+            .prerequisites(vec![
+                FeatPrerequisite::Feat("Improved Critical"),
+                FeatPrerequisite::Feat("Epic Weapon Focus"),
+                FeatPrerequisite::Ability(Ability::Str, 23),
+                CharacterLevel(21),
+            ])
+            .effect(FeatModifiers {
+                hit_damage: Some(Dice::from("1d6")),
+                ..Default::default()
+            }),
+        FeatDefinition::new("Blind Fight").effect(BlindFight),
+        FeatDefinition::new("Bane of Enemies")
+            .prerequisites(vec![ClassLevel(Class::Ranger, 21)])
+            .effect(BaneOfEnemies),
+        FeatDefinition::new("Dual Wielding"),
+        FeatDefinition::new("Epic Dodge")
+            .prerequisites(vec![
+                FeatPrerequisite::Ability(Ability::Dex, 25),
+                CharacterLevel(21),
+            ])
+            .effect(FeatModifiers {
+                dodges_first_hit: true,
+                ..Default::default()
+            }),
+        FeatDefinition::new("Increased Multiplier")
+            .prerequisites(vec![ClassLevel(Class::WeaponMaster, 5)])
+            .effect(FeatModifiers {
+                crit_multiplier: 1,
+                ..Default::default()
+            }),
+        FeatDefinition::new("Improved Critical")
+            .weapon_feat()
+            .prerequisites(vec![BaseAttackBonus(8)])
+            .effect(ImprovedCritical),
+        FeatDefinition::new("Ki Critical")
+            .prerequisites(vec![ClassLevel(Class::WeaponMaster, 7)])
+            .effect(FeatModifiers {
+                threat_range: 2,
+                ..Default::default()
+            }),
+        FeatDefinition::new("Weapon Specialization")
+            .weapon_feat()
+            .prerequisites(vec![
+                FeatPrerequisite::Feat("Weapon Focus"),
+                ClassLevel(Class::Fighter, 4),
+            ])
+            .effect(FeatModifiers {
+                hit_damage: Some(Dice::from(2)),
+                ..Default::default()
+            }),
+        FeatDefinition::new("Epic Weapon Specialization")
+            .weapon_feat()
+            .prerequisites(vec![
+                FeatPrerequisite::Feat("Weapon Specialization"),
+                FeatPrerequisite::Feat("Epic Weapon Focus"),
+                CharacterLevel(21),
+            ])
+            .effect(FeatModifiers {
+                hit_damage: Some(Dice::from(4)),
+                ..Default::default()
+            }),
+        FeatDefinition::new("Monk").prerequisites(vec![ClassLevel(Class::Monk, 1)]),
+        FeatDefinition::new("Weapon Finesse").prerequisites(vec![BaseAttackBonus(1)]),
+        FeatDefinition::new("Zen Archery").prerequisites(vec![
+            FeatPrerequisite::Ability(Ability::Wis, 19),
+            BaseAttackBonus(1),
+        ]),
+        FeatDefinition::new("Weapon Focus")
+            .weapon_feat()
+            .prerequisites(vec![BaseAttackBonus(1)])
+            .effect(FeatModifiers {
+                attack_bonus: 1,
+                ..Default::default()
+            }),
+        FeatDefinition::new("Epic Weapon Focus")
+            .weapon_feat()
+            .prerequisites(vec![
+                FeatPrerequisite::Feat("Weapon Focus"),
+                CharacterLevel(21),
+            ])
+            .effect(FeatModifiers {
+                attack_bonus: 2,
+                ..Default::default()
+            }),
+    ]
+}
+
+type FeatRegistry = RwLock<HashMap<String, Arc<FeatDefinition>>>;
+
+static FEAT_REGISTRY: OnceLock<FeatRegistry> = OnceLock::new();
+
+fn registry() -> &'static FeatRegistry {
+    FEAT_REGISTRY.get_or_init(|| {
+        RwLock::new(
+            feat_list()
+                .into_iter()
+                .map(|x| (x.name.clone(), Arc::new(x)))
+                .collect(),
+        )
+    })
+}
+
+// Adds a feat to the registry for every thread, replacing the feat of the
+// same name if there is one.
+pub fn register_feat(definition: FeatDefinition) {
+    registry()
+        .write()
+        .unwrap()
+        .insert(definition.name.clone(), Arc::new(definition));
+}
+
+pub fn get_feat_definition(name: &str) -> Option<Arc<FeatDefinition>> {
+    registry().read().unwrap().get(name).cloned()
+}
+
+pub fn get_feat_list() -> HashMap<String, Feat> {
+    registry()
+        .read()
+        .unwrap()
+        .keys()
+        .map(|name| (name.clone(), Feat(name.clone())))
+        .collect()
 }
 
 pub fn get_feat(name: &str) -> Feat {
-    get_feat_list().get(name).unwrap().to_owned()
+    get_feat_definition(name)
+        .map(|x| Feat(x.name.clone()))
+        .unwrap()
+}
+
+pub fn is_weapon_feat(name: &str) -> bool {
+    get_feat_definition(name).is_some_and(|x| x.weapon_feat)
+}
+
+// Weapon feat taken for the given weapon base. Panics if the feat can't be
+// taken per weapon.
+pub fn get_weapon_feat(name: &str, weapon_base: &str) -> Feat {
+    assert!(is_weapon_feat(name), "{} is not a weapon feat", name);
+
+    Feat::for_weapon(name, weapon_base)
+}
+
+// Prerequisites of the feat, or of the feat it was taken for a weapon base
+// from. `None` for unknown feats.
+pub fn get_feat_prerequisites(feat: &Feat) -> Option<Vec<FeatPrerequisite>> {
+    get_feat_definition(feat.base_name()).map(|x| x.prerequisites.clone())
 }
@@ -1,3 +1,5 @@
+pub mod effect;
+#[allow(clippy::module_inception)]
 mod feat;
 pub mod feat_db;
 
@@ -0,0 +1,567 @@
+use super::{
+    activated_ability::AbilityState,
+    character::Character,
+    combat::{Combat, CombatStatistics, RoundState},
+    dice::{set_seed, with_rng, Dice},
+    rules::MAX_FIGHT_ROUNDS,
+    statistics::RunningStatistics,
+    string::align_string,
+};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+// Resolves rounds until the defender's hit points are depleted. Returns
+// the number of rounds it took, or `None` if the defender survived
+// `MAX_FIGHT_ROUNDS` rounds. With a seed, the dice are reseeded from it and
+// the round number at the start of every round.
+pub(crate) fn rounds_to_kill(combat: &Combat, seed: Option<u64>) -> Option<i32> {
+    let mut hit_points = combat.defender().hit_points;
+    let mut abilities = AbilityState::new(combat.attacker(), combat.defender());
+
+    for round in 1..=MAX_FIGHT_ROUNDS {
+        if let Some(seed) = seed {
+            set_seed(seed ^ round as u64);
+        }
+
+        hit_points -= combat
+            .resolve_fight_round(&mut RoundState::default(), &mut abilities)
+            .dmg_dealt
+            .total_dmg();
+
+        if hit_points <= 0 {
+            return Some(round);
+        }
+    }
+
+    None
+}
+
+pub(crate) struct DuelOutcome {
+    // Index of the winning side, `None` if nobody died in time.
+    pub winner: Option<usize>,
+    pub rounds: i32,
+    pub remaining_hit_points: [i32; 2],
+}
+
+// Rolls initiative for both sides, with ties going to a coin flip.
+fn roll_initiative(first: &Character, second: &Character) -> [usize; 2] {
+    let d20 = Dice::from("1d20");
+    let first_roll = d20.roll() + first.abilities.dex.get_mod();
+    let second_roll = d20.roll() + second.abilities.dex.get_mod();
+
+    if first_roll > second_roll || (first_roll == second_roll && Dice::from("1d2").roll() == 1) {
+        [0, 1]
+    } else {
+        [1, 0]
+    }
+}
+
+// Both sides attack each other every round in initiative order. The side
+// acting second doesn't get to attack in the round it is killed in.
+pub(crate) fn duel(first: &Character, second: &Character) -> DuelOutcome {
+    let combats = [Combat::new(first, second), Combat::new(second, first)];
+    let mut abilities = [
+        AbilityState::new(first, second),
+        AbilityState::new(second, first),
+    ];
+    let order = roll_initiative(first, second);
+    let mut hit_points = [first.hit_points, second.hit_points];
+
+    for round in 1..=MAX_FIGHT_ROUNDS {
+        for &attacker in &order {
+            let defender = 1 - attacker;
+            hit_points[defender] -= combats[attacker]
+                .resolve_fight_round(&mut RoundState::default(), &mut abilities[attacker])
+                .dmg_dealt
+                .total_dmg();
+
+            if hit_points[defender] <= 0 {
+                return DuelOutcome {
+                    winner: Some(attacker),
+                    rounds: round,
+                    remaining_hit_points: hit_points,
+                };
+            }
+        }
+    }
+
+    DuelOutcome {
+        winner: None,
+        rounds: MAX_FIGHT_ROUNDS,
+        remaining_hit_points: hit_points,
+    }
+}
+
+// Resolves a round of every attacker against the same defender in a random
+// order. The defender's round state is shared, so e.g. epic dodge is spent
+// on whichever attacker triggers it first.
+fn resolve_encounter_round(
+    combats: &[Combat],
+    abilities: &mut [AbilityState],
+) -> Vec<CombatStatistics> {
+    let mut order = (0..combats.len()).collect::<Vec<usize>>();
+    with_rng(|rng| order.shuffle(rng));
+
+    let mut state = RoundState::default();
+    let mut statistics = vec![CombatStatistics::new(); combats.len()];
+
+    for i in order {
+        statistics[i] = combats[i].resolve_fight_round(&mut state, &mut abilities[i]);
+    }
+
+    statistics
+}
+
+// Fights the defender with every attacker until it dies and adds the
+// statistics of each attacker to the result.
+pub(crate) fn encounter(
+    attackers: &[Character],
+    defender: &Character,
+    result: &mut EncounterResult,
+) -> Option<i32> {
+    let combats = attackers
+        .iter()
+        .map(|x| Combat::new(x, defender))
+        .collect::<Vec<Combat>>();
+    let mut abilities = attackers
+        .iter()
+        .map(|x| AbilityState::new(x, defender))
+        .collect::<Vec<AbilityState>>();
+    let mut hit_points = defender.hit_points;
+
+    for round in 1..=MAX_FIGHT_ROUNDS {
+        let round_statistics = resolve_encounter_round(&combats, &mut abilities);
+        let mut round_dmg = 0;
+
+        for (i, statistics) in round_statistics.iter().enumerate() {
+            round_dmg += statistics.dmg_dealt.total_dmg();
+            result.attackers[i].add_from(statistics);
+        }
+
+        hit_points -= round_dmg;
+        result.dmg_per_round.add(round_dmg as f64);
+
+        if hit_points <= 0 {
+            return Some(round);
+        }
+    }
+
+    None
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct TimeToKillResult {
+    rounds: Vec<i32>,
+    unresolved_fights: i32,
+    // Set if the fights weren't simulated as a character had no hit points,
+    // every fight would otherwise end in the first round.
+    #[serde(default)]
+    missing_hit_points: bool,
+}
+
+impl TimeToKillResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, rounds: Option<i32>) {
+        match rounds {
+            Some(rounds) => {
+                let i = self.rounds.partition_point(|x| *x <= rounds);
+                self.rounds.insert(i, rounds);
+            }
+            None => self.unresolved_fights += 1,
+        }
+    }
+
+    pub fn total_fights(&self) -> i32 {
+        self.rounds.len() as i32 + self.unresolved_fights
+    }
+
+    pub fn unresolved_fights(&self) -> i32 {
+        self.unresolved_fights
+    }
+
+    pub fn missing_hit_points(&self) -> bool {
+        self.missing_hit_points
+    }
+
+    pub(crate) fn set_missing_hit_points(&mut self) {
+        self.missing_hit_points = true;
+    }
+
+    // Mean rounds to kill over the fights that ended with a kill.
+    pub fn mean_rounds(&self) -> f64 {
+        if self.rounds.is_empty() {
+            return 0.0;
+        }
+
+        self.rounds.iter().map(|x| *x as f64).sum::<f64>() / self.rounds.len() as f64
+    }
+
+    pub fn median_rounds(&self) -> Option<i32> {
+        self.percentile(50.0)
+    }
+
+    // Nearest-rank percentile of rounds to kill over the fights that
+    // ended with a kill.
+    pub fn percentile(&self, percentile: f64) -> Option<i32> {
+        if self.rounds.is_empty() {
+            return None;
+        }
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * self.rounds.len() as f64).ceil();
+        let i = (rank as usize).clamp(1, self.rounds.len()) - 1;
+
+        Some(self.rounds[i])
+    }
+
+    // Probability of the defender dying within the given number of rounds.
+    pub fn kill_probability(&self, rounds: i32) -> f64 {
+        if self.total_fights() == 0 {
+            return 0.0;
+        }
+
+        self.rounds.partition_point(|x| *x <= rounds) as f64 / self.total_fights() as f64
+    }
+}
+
+impl std::fmt::Display for TimeToKillResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.missing_hit_points {
+            return write!(f, "NO FIGHTS, THE DEFENDER HAS NO HIT POINTS");
+        }
+
+        let format_rounds = |x: Option<i32>| x.map(|x| x.to_string()).unwrap_or("-".into());
+
+        let mut string_list: Vec<String> = vec![
+            align_string("TOTAL FIGHT", self.total_fights().to_string()),
+            align_string("    * UNRESOLVED", self.unresolved_fights.to_string()),
+            "".into(),
+            align_string("MEAN ROUNDS TO KILL", format!("{:.2}", self.mean_rounds())),
+            align_string("MEDIAN ROUNDS TO KILL", format_rounds(self.median_rounds())),
+        ];
+
+        for percentile in [10.0, 25.0, 75.0, 90.0] {
+            string_list.push(align_string(
+                format!("    * {}TH PERCENTILE", percentile).as_str(),
+                format_rounds(self.percentile(percentile)),
+            ));
+        }
+
+        string_list.push("".into());
+
+        for rounds in [1, 2, 3, 5, 10] {
+            string_list.push(align_string(
+                format!("KILL CHANCE IN {} ROUND(S)", rounds).as_str(),
+                format!("{:.2}%", self.kill_probability(rounds) * 100.0),
+            ));
+        }
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct DuelResult {
+    names: [String; 2],
+    wins: [i32; 2],
+    remaining_hit_points: [i64; 2],
+    rounds: TimeToKillResult,
+}
+
+impl DuelResult {
+    pub fn new(first: &Character, second: &Character) -> Self {
+        Self {
+            names: [first.name.clone(), second.name.clone()],
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn add(&mut self, outcome: DuelOutcome) {
+        match outcome.winner {
+            Some(winner) => {
+                self.wins[winner] += 1;
+                self.remaining_hit_points[winner] += outcome.remaining_hit_points[winner] as i64;
+                self.rounds.add(Some(outcome.rounds));
+            }
+            None => self.rounds.add(None),
+        }
+    }
+
+    pub fn total_fights(&self) -> i32 {
+        self.rounds.total_fights()
+    }
+
+    pub(crate) fn set_missing_hit_points(&mut self) {
+        self.rounds.set_missing_hit_points();
+    }
+
+    pub fn draws(&self) -> i32 {
+        self.rounds.unresolved_fights()
+    }
+
+    // Index 0 is the first character passed to the duel, 1 is the second.
+    pub fn wins(&self, side: usize) -> i32 {
+        self.wins[side]
+    }
+
+    pub fn win_probability(&self, side: usize) -> f64 {
+        if self.total_fights() == 0 {
+            return 0.0;
+        }
+
+        self.wins[side] as f64 / self.total_fights() as f64
+    }
+
+    // Average hit points the side has left over the duels it won.
+    pub fn average_remaining_hit_points(&self, side: usize) -> f64 {
+        if self.wins[side] == 0 {
+            return 0.0;
+        }
+
+        self.remaining_hit_points[side] as f64 / self.wins[side] as f64
+    }
+
+    // Distribution of rounds it took to resolve the duels, draws are
+    // counted as unresolved fights.
+    pub fn rounds(&self) -> &TimeToKillResult {
+        &self.rounds
+    }
+}
+
+impl std::fmt::Display for DuelResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.rounds.missing_hit_points() {
+            return write!(f, "NO FIGHTS, A CHARACTER HAS NO HIT POINTS");
+        }
+
+        let mut string_list: Vec<String> = vec![
+            align_string("TOTAL FIGHT", self.total_fights().to_string()),
+            align_string("    * DRAW", self.draws().to_string()),
+            "".into(),
+        ];
+
+        for side in 0..2 {
+            string_list.push(self.names[side].to_uppercase());
+            string_list.push(align_string(
+                "    * WIN CHANCE",
+                format!("{:.2}%", self.win_probability(side) * 100.0),
+            ));
+            string_list.push(align_string(
+                "    * AVERAGE REMAINING HP",
+                format!("{:.2}", self.average_remaining_hit_points(side)),
+            ));
+            string_list.push("".into());
+        }
+
+        string_list.push(align_string(
+            "MEAN ROUNDS TO RESOLVE",
+            format!("{:.2}", self.rounds.mean_rounds()),
+        ));
+        string_list.push(align_string(
+            "MEDIAN ROUNDS TO RESOLVE",
+            self.rounds
+                .median_rounds()
+                .map(|x| x.to_string())
+                .unwrap_or("-".into()),
+        ));
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct EncounterResult {
+    names: Vec<String>,
+    attackers: Vec<CombatStatistics>,
+    dmg_per_round: RunningStatistics,
+    time_to_kill: TimeToKillResult,
+}
+
+impl EncounterResult {
+    pub fn new(attackers: &[Character]) -> Self {
+        Self {
+            names: attackers.iter().map(|x| x.name.clone()).collect(),
+            attackers: vec![CombatStatistics::new(); attackers.len()],
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn add(&mut self, rounds: Option<i32>) {
+        self.time_to_kill.add(rounds);
+    }
+
+    pub(crate) fn set_missing_hit_points(&mut self) {
+        self.time_to_kill.set_missing_hit_points();
+    }
+
+    pub fn total_rounds(&self) -> i64 {
+        self.dmg_per_round.count()
+    }
+
+    // Statistics of the attacker at the given index in the attacker list.
+    pub fn attacker(&self, i: usize) -> Option<&CombatStatistics> {
+        self.attackers.get(i)
+    }
+
+    pub fn combined(&self) -> CombatStatistics {
+        let mut statistics = CombatStatistics::new();
+
+        for attacker in &self.attackers {
+            statistics.add_from(attacker);
+        }
+
+        // Attackers share the same rounds, so merging their per round damage
+        // would count every round once per attacker.
+        statistics.dmg_per_round = self.dmg_per_round;
+        statistics
+    }
+
+    pub fn time_to_kill(&self) -> &TimeToKillResult {
+        &self.time_to_kill
+    }
+}
+
+impl std::fmt::Display for EncounterResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.time_to_kill.missing_hit_points() {
+            return write!(f, "{}", self.time_to_kill);
+        }
+
+        let mut string_list: Vec<String> = vec![];
+
+        for (name, statistics) in self.names.iter().zip(self.attackers.iter()) {
+            string_list.push(name.to_uppercase());
+            string_list.push("".into());
+            string_list.push(statistics.to_string());
+            string_list.push("".into());
+            string_list.push(statistics.dmg_per_round_string());
+            string_list.push("".into());
+            string_list.push("=".repeat(50));
+            string_list.push("".into());
+        }
+
+        let combined = self.combined();
+
+        string_list.push("COMBINED".into());
+        string_list.push("".into());
+        string_list.push(combined.to_string());
+        string_list.push("".into());
+        string_list.push(combined.dmg_per_round_string());
+        string_list.push("".into());
+        string_list.push(self.time_to_kill.to_string());
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{duel, encounter, DuelResult, EncounterResult, TimeToKillResult};
+    use crate::{
+        character::{AbilityList, Character, CharacterBuilder},
+        feat::feat_db::get_feat,
+        item::{weapon_db::get_weapon_base, Weapon},
+    };
+
+    #[test]
+    fn time_to_kill_result() {
+        let mut result = TimeToKillResult::new();
+
+        for rounds in [3, 1, 2, 2, 5, 4, 2, 3, 1] {
+            result.add(Some(rounds));
+        }
+        result.add(None);
+
+        assert_eq!(result.total_fights(), 10);
+        assert_eq!(result.unresolved_fights(), 1);
+        assert_eq!(result.mean_rounds(), 23.0 / 9.0);
+        assert_eq!(result.median_rounds(), Some(2));
+        assert_eq!(result.percentile(0.0), Some(1));
+        assert_eq!(result.percentile(100.0), Some(5));
+        assert_eq!(result.kill_probability(0), 0.0);
+        assert_eq!(result.kill_probability(2), 0.5);
+        assert_eq!(result.kill_probability(5), 0.9);
+
+        let result = TimeToKillResult::new();
+        assert_eq!(result.mean_rounds(), 0.0);
+        assert_eq!(result.median_rounds(), None);
+        assert_eq!(result.kill_probability(1), 0.0);
+    }
+
+    #[test]
+    fn duel_result() {
+        let first = Character::builder()
+            .name("First".into())
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(100)
+            .base_apr(1)
+            .hit_points(1000)
+            .physical_damage_reduction(100)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let second = Character::builder()
+            .name("Second".into())
+            .base_apr(1)
+            .hit_points(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+
+        let mut result = DuelResult::new(&first, &second);
+
+        for _ in 0..10 {
+            result.add(duel(&first, &second));
+        }
+
+        assert_eq!(result.total_fights(), 10);
+        assert_eq!(result.wins(0), 10);
+        assert_eq!(result.wins(1), 0);
+        assert_eq!(result.win_probability(1), 0.0);
+        assert_eq!(result.average_remaining_hit_points(0), 1000.0);
+    }
+
+    #[test]
+    fn encounter_result() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(100)
+            .base_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let attackers = vec![attacker.clone(), attacker];
+        let defender = Character::builder()
+            .hit_points(1000)
+            .feats(vec![get_feat("Epic Dodge"), get_feat("Critical Immunity")])
+            .build();
+
+        let mut result = EncounterResult::new(&attackers);
+        let rounds = encounter(&attackers, &defender, &mut result);
+        result.add(rounds);
+
+        assert_eq!(result.time_to_kill().total_fights(), 1);
+        assert_eq!(result.total_rounds(), rounds.unwrap() as i64);
+
+        // Defender can only epic dodge a single attack per round, no matter
+        // how many attackers there are.
+        let combined = result.combined();
+        assert!(combined.epic_dodged_attacks <= result.total_rounds());
+        assert!(combined.epic_dodged_attacks >= result.total_rounds() * 9 / 10);
+        assert_eq!(combined.total_attacks(), result.total_rounds() * 2);
+        assert_eq!(combined.total_rounds(), result.total_rounds());
+        assert!(
+            (combined.dmg_per_round.mean() * result.total_rounds() as f64
+                - combined.dmg_dealt.total_dmg() as f64)
+                .abs()
+                < 1e-6
+        );
+        assert_eq!(
+            result.attacker(0).unwrap().total_attacks(),
+            result.total_rounds()
+        );
+
+        let defender = CharacterBuilder::from(defender).hit_points(1).build();
+        let mut result = EncounterResult::new(&attackers);
+        assert!(encounter(&attackers, &defender, &mut result).is_some());
+    }
+}
@@ -30,10 +30,7 @@ impl DamageType {
     }
 
     pub fn is_physical(&self) -> bool {
-        match self {
-            Self::Slashing | Self::Piercing | Self::Bludgeoning => true,
-            _ => false,
-        }
+        matches!(self, Self::Slashing | Self::Piercing | Self::Bludgeoning)
     }
 }
 
@@ -67,7 +64,7 @@ impl std::fmt::Display for DamageType {
     }
 }
 
-#[derive(PartialEq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Damage {
     amount: Dice,
     pub type_: DamageType,
@@ -77,7 +74,7 @@ pub struct Damage {
 
 impl std::fmt::Display for Damage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.amount.to_string())
+        write!(f, "{}", self.amount)
     }
 }
 
@@ -158,7 +155,7 @@ impl DamageResult {
     }
 
     pub fn total_dmg(&self) -> i32 {
-        self.0.borrow().iter().map(|(_, v)| v).sum()
+        self.0.borrow().values().sum()
     }
 
     pub fn add_from(&mut self, other: &DamageResult) {
@@ -194,15 +191,9 @@ mod test {
         assert_eq!(dmg_result.total_dmg(), 14);
 
         assert_eq!(dmg_result.get_types().len(), 3);
-        assert_eq!(dmg_result.get_types().contains(&DamageType::Acid), true);
-        assert_eq!(
-            dmg_result.get_types().contains(&DamageType::Bludgeoning),
-            true
-        );
-        assert_eq!(dmg_result.get_types().contains(&DamageType::Cold), true);
-        assert_eq!(
-            dmg_result.get_types().contains(&DamageType::Slashing),
-            false
-        );
+        assert!(dmg_result.get_types().contains(&DamageType::Acid));
+        assert!(dmg_result.get_types().contains(&DamageType::Bludgeoning));
+        assert!(dmg_result.get_types().contains(&DamageType::Cold));
+        assert!(!dmg_result.get_types().contains(&DamageType::Slashing));
     }
 }
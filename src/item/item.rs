@@ -1,16 +1,16 @@
 use super::Damage;
 use crate::dice::Dice;
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
 
 pub fn get_keen_increase(threat_range: i32) -> i32 {
     20 - threat_range + 1
 }
 
-#[derive(PartialEq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 #[allow(unused)]
 pub enum ItemProperty {
-    AttackBonus(i32),      // Not implemented, it won't increase the character's AB.
-    EnchantmentBonus(i32), // Not implemented, it won't increase the character's AB but it will increase the damage.
+    AttackBonus(i32), // Only counted by `AttackBonusBreakdown`, combat uses the character's AB as is.
+    EnchantmentBonus(i32), // Only counted by `AttackBonusBreakdown` for AB, it does increase the damage.
     DamageBonus(Damage),
     MassiveCrit(Dice),
     Keen,
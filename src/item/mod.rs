@@ -1,4 +1,5 @@
 mod damage;
+#[allow(clippy::module_inception)]
 mod item;
 mod weapon;
 pub mod weapon_db;
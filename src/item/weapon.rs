@@ -1,6 +1,6 @@
 use super::{get_keen_increase, DamageType, ItemProperty};
 use crate::{dice::Dice, size::SizeCategory};
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct WeaponBase {
@@ -12,6 +12,19 @@ pub struct WeaponBase {
     pub damage_type: Vec<DamageType>,
 }
 
+// Weapons that are thrown or fire ammunition.
+const RANGED_WEAPON_LIST: &[&str] = &[
+    "Throwing Dagger",
+    "Throwing Axe",
+    "Shuriken",
+    "Dart",
+    "Sling",
+    "Longbow",
+    "Shortbow",
+    "Heavy Crossbow",
+    "Light Crossbow",
+];
+
 impl WeaponBase {
     pub fn new(
         name: String,
@@ -30,9 +43,13 @@ impl WeaponBase {
             damage_type,
         }
     }
+
+    pub fn is_ranged(&self) -> bool {
+        RANGED_WEAPON_LIST.contains(&self.name.as_str())
+    }
 }
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Weapon {
     pub name: String,
     pub base: WeaponBase,
@@ -48,6 +65,20 @@ impl Weapon {
         }
     }
 
+    // Highest of the enchantment and attack bonus properties, as they don't
+    // stack with each other.
+    pub fn attack_bonus(&self) -> i32 {
+        self.item_properties
+            .iter()
+            .map(|x| match x {
+                ItemProperty::AttackBonus(value) | ItemProperty::EnchantmentBonus(value) => *value,
+                _ => 0,
+            })
+            .max()
+            .unwrap_or(0)
+            .max(0)
+    }
+
     pub fn is_keen(&self) -> bool {
         self.item_properties.contains(&ItemProperty::Keen)
     }
@@ -66,35 +97,23 @@ impl Weapon {
     }
 
     pub fn crit_multiplier_override(&self) -> Option<i32> {
-        for override_val in self
-            .item_properties
+        self.item_properties
             .iter()
             .map(|x| match x {
                 ItemProperty::CriticalMultiplierOverride(value) => *value,
                 _ => 0,
             })
-            .filter(|x| *x > 0)
-        {
-            return Some(override_val);
-        }
-
-        None
+            .find(|x| *x > 0)
     }
 
     pub fn threat_range_override(&self) -> Option<i32> {
-        for override_val in self
-            .item_properties
+        self.item_properties
             .iter()
             .map(|x| match x {
                 ItemProperty::ThreatRangeOverride(value) => *value,
                 _ => 0,
             })
-            .filter(|x| *x > 0)
-        {
-            return Some(override_val);
-        }
-
-        None
+            .find(|x| *x > 0)
     }
 }
 
@@ -127,7 +146,7 @@ mod test {
     #[test]
     fn weapon() {
         let weapon = Weapon::new("".into(), get_weapon_base("Rapier"), vec![]);
-        assert_eq!(weapon.is_keen(), false);
+        assert!(!weapon.is_keen());
         assert_eq!(weapon.threat_range(), 18);
 
         // Keen test: 18-20
@@ -143,7 +162,7 @@ mod test {
             ),
             vec![ItemProperty::Keen],
         );
-        assert_eq!(weapon.is_keen(), true);
+        assert!(weapon.is_keen());
         assert_eq!(weapon.threat_range(), 15);
 
         // Keen test: 19-20
@@ -159,7 +178,7 @@ mod test {
             ),
             vec![ItemProperty::Keen],
         );
-        assert_eq!(weapon.is_keen(), true);
+        assert!(weapon.is_keen());
         assert_eq!(weapon.threat_range(), 17);
 
         // Keen test: 20
@@ -175,7 +194,7 @@ mod test {
             ),
             vec![ItemProperty::Keen],
         );
-        assert_eq!(weapon.is_keen(), true);
+        assert!(weapon.is_keen());
         assert_eq!(weapon.threat_range(), 19);
 
         // Threat range and critical multiplier override test
@@ -1,13 +1,31 @@
 extern crate self as arelith;
 
+pub mod activated_ability;
+pub mod alignment;
+pub mod armor_class;
+pub mod attack_bonus;
+pub mod breakpoint;
 pub mod character;
+pub mod class;
 mod combat;
+pub mod damage_hook;
+pub mod damage_source;
+pub mod defense;
 pub mod dice;
 pub mod feat;
+pub mod fight;
 pub mod item;
+pub mod optimizer;
+pub mod race;
 mod rules;
+pub mod sensitivity;
 pub mod simulator;
 pub mod size;
+pub mod statistics;
 mod string;
+pub mod target;
+pub mod validation;
 
-pub use combat::{AttackInfo, AttackType, CombatStatistics, HitResult};
+pub use combat::{
+    AttackContext, AttackInfo, AttackSlotStatistics, AttackType, CombatStatistics, HitResult,
+};
@@ -0,0 +1,500 @@
+use super::{
+    character::{Character, CharacterBuilder},
+    dice::with_rng,
+    feat::Feat,
+    item::{ItemProperty, Weapon, WeaponBase},
+    rules::{MAX_EXHAUSTIVE_BUILDS, OPTIMIZER_RESTARTS},
+    size::SizeCategory,
+    string::align_string,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Candidates and constraints an optimizer picks a build from. Empty
+// weapon base or item property lists keep the template's weapon.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BuildSpace {
+    pub feats: Vec<Feat>,
+    pub weapon_bases: Vec<WeaponBase>,
+    pub item_property_sets: Vec<Vec<ItemProperty>>,
+    // Maximum number of feats picked from the candidate feats.
+    pub max_feats: usize,
+    // Weapon sizes the build is allowed to wield. Empty allows every size.
+    pub weapon_sizes: Vec<SizeCategory>,
+    // Overrides the template's AB.
+    pub ab: Option<i32>,
+}
+
+impl BuildSpace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feats(mut self, feats: impl IntoIterator<Item = Feat>) -> Self {
+        self.feats = feats.into_iter().collect();
+        self
+    }
+
+    pub fn weapon_bases(mut self, weapon_bases: impl IntoIterator<Item = WeaponBase>) -> Self {
+        self.weapon_bases = weapon_bases.into_iter().collect();
+        self
+    }
+
+    pub fn item_property_sets(
+        mut self,
+        item_property_sets: impl IntoIterator<Item = Vec<ItemProperty>>,
+    ) -> Self {
+        self.item_property_sets = item_property_sets.into_iter().collect();
+        self
+    }
+
+    pub fn max_feats(mut self, max_feats: usize) -> Self {
+        self.max_feats = max_feats;
+        self
+    }
+
+    pub fn weapon_sizes(mut self, weapon_sizes: impl IntoIterator<Item = SizeCategory>) -> Self {
+        self.weapon_sizes = weapon_sizes.into_iter().collect();
+        self
+    }
+
+    pub fn ab(mut self, ab: i32) -> Self {
+        self.ab = Some(ab);
+        self
+    }
+
+    fn allowed_weapon_bases(&self, template: &Character) -> Vec<WeaponBase> {
+        let weapon_bases = if self.weapon_bases.is_empty() {
+            vec![template.weapon.base.clone()]
+        } else {
+            self.weapon_bases.clone()
+        };
+
+        weapon_bases
+            .into_iter()
+            .filter(|x| self.weapon_sizes.is_empty() || self.weapon_sizes.contains(&x.size))
+            .collect()
+    }
+
+    fn allowed_item_property_sets(&self, template: &Character) -> Vec<Vec<ItemProperty>> {
+        if self.item_property_sets.is_empty() {
+            vec![template.weapon.item_properties.clone()]
+        } else {
+            self.item_property_sets.clone()
+        }
+    }
+}
+
+// A point in the build space, as indices into its candidate lists.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Candidate {
+    feats: Vec<bool>,
+    weapon_base: usize,
+    item_properties: usize,
+}
+
+impl Candidate {
+    fn feat_count(&self) -> usize {
+        self.feats.iter().filter(|x| **x).count()
+    }
+}
+
+struct Search<'a> {
+    template: &'a Character,
+    space: &'a BuildSpace,
+    weapon_bases: Vec<WeaponBase>,
+    item_property_sets: Vec<Vec<ItemProperty>>,
+    max_feats: usize,
+    evaluated: HashMap<Candidate, f64>,
+}
+
+impl<'a> Search<'a> {
+    fn character(&self, candidate: &Candidate) -> Character {
+        let mut builder = CharacterBuilder::from(self.template.clone()).weapon(Weapon::new(
+            self.template.weapon.name.clone(),
+            self.weapon_bases[candidate.weapon_base].clone(),
+            self.item_property_sets[candidate.item_properties].clone(),
+        ));
+
+        for (feat, _) in self
+            .space
+            .feats
+            .iter()
+            .zip(&candidate.feats)
+            .filter(|(_, x)| **x)
+        {
+            if !self.template.has_feat(feat.clone()) {
+                builder = builder.add_feat(feat.clone());
+            }
+        }
+
+        if let Some(ab) = self.space.ab {
+            builder = builder.ab(ab);
+        }
+
+        builder.build()
+    }
+
+    fn evaluate(&mut self, candidate: &Candidate, f: &mut impl FnMut(&Character) -> f64) -> f64 {
+        if let Some(value) = self.evaluated.get(candidate) {
+            return *value;
+        }
+
+        let value = f(&self.character(candidate));
+        self.evaluated.insert(candidate.clone(), value);
+
+        value
+    }
+
+    // Number of builds in the space, saturating at `u128::MAX`.
+    fn size(&self) -> u128 {
+        let n = self.space.feats.len() as u128;
+        let mut feat_combinations: u128 = 0;
+        let mut binomial: u128 = 1;
+
+        for k in 0..=self.max_feats as u128 {
+            feat_combinations = feat_combinations.saturating_add(binomial);
+            binomial = binomial.saturating_mul(n - k) / (k + 1);
+        }
+
+        feat_combinations
+            .saturating_mul(self.weapon_bases.len() as u128)
+            .saturating_mul(self.item_property_sets.len() as u128)
+    }
+
+    fn feat_combinations(&self) -> Vec<Vec<bool>> {
+        fn combine(
+            picked: &mut Vec<bool>,
+            remaining: usize,
+            total: usize,
+            result: &mut Vec<Vec<bool>>,
+        ) {
+            if picked.len() == total {
+                result.push(picked.clone());
+                return;
+            }
+
+            picked.push(false);
+            combine(picked, remaining, total, result);
+            picked.pop();
+
+            if remaining > 0 {
+                picked.push(true);
+                combine(picked, remaining - 1, total, result);
+                picked.pop();
+            }
+        }
+
+        let mut result = vec![];
+        combine(
+            &mut vec![],
+            self.max_feats,
+            self.space.feats.len(),
+            &mut result,
+        );
+
+        result
+    }
+
+    fn exhaustive(&mut self, f: &mut impl FnMut(&Character) -> f64) {
+        for feats in self.feat_combinations() {
+            for weapon_base in 0..self.weapon_bases.len() {
+                for item_properties in 0..self.item_property_sets.len() {
+                    self.evaluate(
+                        &Candidate {
+                            feats: feats.clone(),
+                            weapon_base,
+                            item_properties,
+                        },
+                        f,
+                    );
+                }
+            }
+        }
+    }
+
+    fn random_candidate(&self) -> Candidate {
+        with_rng(|rng| {
+            let mut feats = vec![false; self.space.feats.len()];
+            let feat_count = rng.gen_range(0..=self.max_feats);
+
+            for i in rand::seq::index::sample(rng, feats.len(), feat_count) {
+                feats[i] = true;
+            }
+
+            Candidate {
+                feats,
+                weapon_base: rng.gen_range(0..self.weapon_bases.len()),
+                item_properties: rng.gen_range(0..self.item_property_sets.len()),
+            }
+        })
+    }
+
+    // Builds that differ from the candidate by a single feat, feat swap,
+    // weapon base or item property set.
+    fn neighbours(&self, candidate: &Candidate) -> Vec<Candidate> {
+        let mut result = vec![];
+        let feat_count = candidate.feat_count();
+
+        for i in 0..candidate.feats.len() {
+            if candidate.feats[i] || feat_count < self.max_feats {
+                let mut neighbour = candidate.clone();
+                neighbour.feats[i] = !neighbour.feats[i];
+                result.push(neighbour);
+            }
+
+            if candidate.feats[i] {
+                for j in (0..candidate.feats.len()).filter(|j| !candidate.feats[*j]) {
+                    let mut neighbour = candidate.clone();
+                    neighbour.feats[i] = false;
+                    neighbour.feats[j] = true;
+                    result.push(neighbour);
+                }
+            }
+        }
+
+        for weapon_base in (0..self.weapon_bases.len()).filter(|x| *x != candidate.weapon_base) {
+            result.push(Candidate {
+                weapon_base,
+                ..candidate.clone()
+            });
+        }
+
+        for item_properties in
+            (0..self.item_property_sets.len()).filter(|x| *x != candidate.item_properties)
+        {
+            result.push(Candidate {
+                item_properties,
+                ..candidate.clone()
+            });
+        }
+
+        result
+    }
+
+    // Steepest ascent hill climbing from random starting builds.
+    fn hill_climb(&mut self, f: &mut impl FnMut(&Character) -> f64) {
+        // Starting builds are drawn up front since evaluating a build may
+        // reseed the dice.
+        let starts = (0..OPTIMIZER_RESTARTS)
+            .map(|_| self.random_candidate())
+            .collect::<Vec<Candidate>>();
+
+        for mut current in starts {
+            let mut current_value = self.evaluate(&current, f);
+
+            loop {
+                let mut best = None;
+
+                for neighbour in self.neighbours(&current) {
+                    let value = self.evaluate(&neighbour, f);
+
+                    if value > current_value {
+                        current_value = value;
+                        best = Some(neighbour);
+                    }
+                }
+
+                match best {
+                    Some(neighbour) => current = neighbour,
+                    None => break,
+                }
+            }
+        }
+    }
+}
+
+// Searches the build space for the builds that score the highest. Small
+// spaces are searched exhaustively, large ones with hill climbing.
+pub(crate) fn optimize(
+    template: &Character,
+    space: &BuildSpace,
+    top_n: usize,
+    mut f: impl FnMut(&Character) -> f64,
+) -> OptimizerResult {
+    let mut search = Search {
+        template,
+        space,
+        weapon_bases: space.allowed_weapon_bases(template),
+        item_property_sets: space.allowed_item_property_sets(template),
+        max_feats: space.max_feats.min(space.feats.len()),
+        evaluated: HashMap::new(),
+    };
+    let mut result = OptimizerResult::default();
+
+    if search.weapon_bases.is_empty() {
+        return result;
+    }
+
+    result.exhaustive = search.size() <= MAX_EXHAUSTIVE_BUILDS as u128;
+
+    if result.exhaustive {
+        search.exhaustive(&mut f);
+    } else {
+        search.hill_climb(&mut f);
+    }
+
+    let mut evaluated = search.evaluated.iter().collect::<Vec<(&Candidate, &f64)>>();
+    evaluated.sort_by(|a, b| b.1.total_cmp(a.1));
+
+    result.evaluated_builds = evaluated.len();
+    result.builds = evaluated
+        .into_iter()
+        .take(top_n)
+        .map(|(candidate, value)| OptimizedBuild {
+            character: search.character(candidate),
+            dmg_per_round: *value,
+        })
+        .collect();
+
+    result
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OptimizedBuild {
+    pub character: Character,
+    pub dmg_per_round: f64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct OptimizerResult {
+    builds: Vec<OptimizedBuild>,
+    evaluated_builds: usize,
+    exhaustive: bool,
+}
+
+impl OptimizerResult {
+    // Best builds first.
+    pub fn builds(&self) -> &Vec<OptimizedBuild> {
+        &self.builds
+    }
+
+    pub fn evaluated_builds(&self) -> usize {
+        self.evaluated_builds
+    }
+
+    // Whether every build in the space was evaluated, so the builds are
+    // guaranteed to be the best ones.
+    pub fn is_exhaustive(&self) -> bool {
+        self.exhaustive
+    }
+}
+
+impl std::fmt::Display for OptimizerResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string_list = vec![
+            align_string(
+                "EVALUATED BUILDS",
+                format!(
+                    "{} ({})",
+                    self.evaluated_builds,
+                    if self.exhaustive {
+                        "exhaustive"
+                    } else {
+                        "hill climbing"
+                    }
+                ),
+            ),
+            "".into(),
+        ];
+
+        for (i, build) in self.builds.iter().enumerate() {
+            string_list.push(align_string(
+                &format!("#{}", i + 1),
+                format!("{:.2} DPR", build.dmg_per_round),
+            ));
+            string_list.push(align_string(
+                "    * WEAPON",
+                build.character.weapon.base.name.clone(),
+            ));
+            string_list.push(align_string(
+                "    * FEATS",
+                build
+                    .character
+                    .feats
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ));
+        }
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BuildSpace;
+    use crate::{
+        character::{AbilityList, Character},
+        feat::feat_db::get_feat,
+        item::{weapon_db::get_weapon_base, ItemProperty, Weapon},
+        simulator::CombatSimulator,
+        size::SizeCategory,
+        target::TargetProfile,
+    };
+
+    #[test]
+    fn optimizer() {
+        let template = Character::builder()
+            .abilities(AbilityList::builder().str(20).build())
+            .ab(40)
+            .base_apr(4)
+            .weapon(Weapon::new("".into(), get_weapon_base("Dagger"), vec![]))
+            .build();
+        let space = BuildSpace::new()
+            .feats(vec![
+                get_feat("Weapon Specialization"),
+                get_feat("Epic Weapon Specialization"),
+                get_feat("Blind Fight"),
+            ])
+            .weapon_bases(vec![
+                get_weapon_base("Dagger"),
+                get_weapon_base("Greatsword"),
+                get_weapon_base("Longsword"),
+            ])
+            .item_property_sets(vec![vec![], vec![ItemProperty::Keen]])
+            .max_feats(2)
+            .weapon_sizes(vec![SizeCategory::Tiny, SizeCategory::Medium])
+            .ab(45);
+        let simulator = CombatSimulator::new(100);
+        let defender = TargetProfile::new(40).to_character();
+
+        let result = simulator.optimize(&template, &defender, &space, 3);
+        assert!(result.is_exhaustive());
+        // 7 feat combinations, 2 weapons and 2 item property sets.
+        assert_eq!(result.evaluated_builds(), 28);
+        assert_eq!(result.builds().len(), 3);
+
+        let best = &result.builds()[0];
+        assert_eq!(best.character.ab, 45);
+        assert_eq!(best.character.weapon.base.name, "Longsword");
+        assert!(best.character.has_weapon_spec());
+        assert!(best.character.has_epic_weapon_spec());
+        assert!(result.builds()[0].dmg_per_round >= result.builds()[2].dmg_per_round);
+
+        // Feats registered by other tests would change the size of the
+        // space, so the feats are listed explicitly.
+        let space = space
+            .feats(
+                [
+                    "Weapon Specialization",
+                    "Epic Weapon Specialization",
+                    "Blind Fight",
+                    "Improved Critical",
+                    "Overwhelming Critical",
+                    "Weapon Focus",
+                    "Epic Weapon Focus",
+                    "Dual Wielding",
+                ]
+                .map(get_feat),
+            )
+            .max_feats(usize::MAX);
+        let simulator = CombatSimulator::new(1);
+        let result = simulator.optimize(&template, &defender, &space, 5);
+        assert!(!result.is_exhaustive());
+        assert_eq!(result.builds().len(), 5);
+    }
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Copy, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum RacialType {
+    Aberration,
+    Animal,
+    Beast,
+    Construct,
+    Dragon,
+    Dwarf,
+    Elemental,
+    Elf,
+    Fey,
+    Giant,
+    Gnome,
+    Goblinoid,
+    HalfElf,
+    Halfling,
+    HalfOrc,
+    #[default]
+    Human,
+    MagicalBeast,
+    Monstrous,
+    Orc,
+    Outsider,
+    Reptilian,
+    Shapechanger,
+    Undead,
+    Vermin,
+}
+
+impl std::fmt::Display for RacialType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Aberration => "Aberration",
+            Self::Animal => "Animal",
+            Self::Beast => "Beast",
+            Self::Construct => "Construct",
+            Self::Dragon => "Dragon",
+            Self::Dwarf => "Dwarf",
+            Self::Elemental => "Elemental",
+            Self::Elf => "Elf",
+            Self::Fey => "Fey",
+            Self::Giant => "Giant",
+            Self::Gnome => "Gnome",
+            Self::Goblinoid => "Goblinoid",
+            Self::HalfElf => "Half-Elf",
+            Self::Halfling => "Halfling",
+            Self::HalfOrc => "Half-Orc",
+            Self::Human => "Human",
+            Self::MagicalBeast => "Magical Beast",
+            Self::Monstrous => "Monstrous",
+            Self::Orc => "Orc",
+            Self::Outsider => "Outsider",
+            Self::Reptilian => "Reptilian",
+            Self::Shapechanger => "Shapechanger",
+            Self::Undead => "Undead",
+            Self::Vermin => "Vermin",
+        };
+
+        write!(f, "{}", name)
+    }
+}
@@ -1,2 +1,12 @@
 pub static CONSECUTIVE_ATTACK_AB_PENALTY: i32 = 5;
 pub static MONK_CONSECUTIVE_ATTACK_AB_PENALTY: i32 = 3;
+pub static EPIC_LEVEL: i32 = 20;
+pub static KNOCKDOWN_AC_PENALTY: i32 = 4;
+// Maximum AB granted by item properties and effects together.
+pub static MAX_EFFECT_AB: i32 = 20;
+pub static MAX_BASE_APR: i32 = 4;
+pub static MAX_MONK_BASE_APR: i32 = 6;
+pub static MAX_FIGHT_ROUNDS: i32 = 1000;
+pub static DEFAULT_PRECISION_BATCH_SIZE: i32 = 1000;
+pub static MAX_EXHAUSTIVE_BUILDS: i32 = 1000;
+pub static OPTIMIZER_RESTARTS: i32 = 10;
@@ -0,0 +1,280 @@
+use super::{
+    character::{Character, CharacterBuilder},
+    feat::Feat,
+    item::{Damage, ItemProperty},
+    string::align_string,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::max;
+
+// A single change to a build whose effect is measured in isolation.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum Perturbation {
+    AttackBonus(i32),
+    Strength(i32),
+    ExtraApr(i32),
+    Feat(Feat),
+    // Widens the weapon's threat range by the given amount.
+    ThreatRange(i32),
+    CritMultiplier(i32),
+    // Adds another damage bonus property to the weapon.
+    DamageBonus(Damage),
+}
+
+impl Perturbation {
+    // How many units of the knob the perturbation adds, used to report the
+    // change per unit.
+    pub fn units(&self) -> i32 {
+        match self {
+            Self::AttackBonus(n)
+            | Self::Strength(n)
+            | Self::ExtraApr(n)
+            | Self::ThreatRange(n)
+            | Self::CritMultiplier(n) => *n,
+            Self::Feat(_) | Self::DamageBonus(_) => 1,
+        }
+    }
+
+    pub fn apply(&self, character: &Character) -> Character {
+        let mut character = character.clone();
+
+        match self {
+            Self::AttackBonus(n) => character.ab += n,
+            Self::Strength(n) => {
+                character.abilities.str = (character.abilities.str.value() + n).into()
+            }
+            Self::ExtraApr(n) => character.extra_apr += n,
+            Self::Feat(feat) => {
+                if !character.has_feat(feat.clone()) {
+                    character = CharacterBuilder::from(character)
+                        .add_feat(feat.clone())
+                        .build();
+                }
+            }
+            Self::ThreatRange(n) => {
+                let threat_range = max(1, character.weapon_threat_range() - n);
+
+                character
+                    .weapon
+                    .item_properties
+                    .retain(|x| !matches!(x, ItemProperty::ThreatRangeOverride(_)));
+                character
+                    .weapon
+                    .item_properties
+                    .push(ItemProperty::ThreatRangeOverride(threat_range));
+            }
+            Self::CritMultiplier(n) => {
+                let crit_multiplier = character.weapon_crit_multiplier() + n;
+
+                character
+                    .weapon
+                    .item_properties
+                    .retain(|x| !matches!(x, ItemProperty::CriticalMultiplierOverride(_)));
+                character
+                    .weapon
+                    .item_properties
+                    .push(ItemProperty::CriticalMultiplierOverride(crit_multiplier));
+            }
+            Self::DamageBonus(damage) => character
+                .weapon
+                .item_properties
+                .push(ItemProperty::DamageBonus(damage.clone())),
+        }
+
+        character
+    }
+}
+
+impl std::fmt::Display for Perturbation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AttackBonus(n) => write!(f, "{:+} AB", n),
+            Self::Strength(n) => write!(f, "{:+} STR", n),
+            Self::ExtraApr(n) => write!(f, "{:+} APR", n),
+            Self::Feat(feat) => write!(f, "{}", feat),
+            Self::ThreatRange(n) => write!(f, "{:+} THREAT RANGE", n),
+            Self::CritMultiplier(n) => write!(f, "{:+} CRIT MULTIPLIER", n),
+            Self::DamageBonus(damage) => write!(f, "{} {}", damage, damage.type_),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SensitivityMetric {
+    DamagePerRound,
+    // Mean rounds to kill the defender over the given number of fights.
+    TimeToKill(i32),
+}
+
+impl std::fmt::Display for SensitivityMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DamagePerRound => write!(f, "DPR"),
+            Self::TimeToKill(_) => write!(f, "ROUNDS TO KILL"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SensitivityEntry {
+    pub perturbation: Perturbation,
+    pub value: f64,
+    pub change: f64,
+}
+
+impl SensitivityEntry {
+    pub fn change_per_unit(&self) -> f64 {
+        let units = self.perturbation.units();
+
+        if units == 0 {
+            return 0.0;
+        }
+
+        self.change / units as f64
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SensitivityResult {
+    metric: SensitivityMetric,
+    baseline: f64,
+    entries: Vec<SensitivityEntry>,
+}
+
+impl SensitivityResult {
+    pub fn new(metric: SensitivityMetric, baseline: f64) -> Self {
+        Self {
+            metric,
+            baseline,
+            entries: vec![],
+        }
+    }
+
+    pub fn add(&mut self, perturbation: Perturbation, value: f64) {
+        self.entries.push(SensitivityEntry {
+            perturbation,
+            value,
+            change: value - self.baseline,
+        });
+    }
+
+    pub fn metric(&self) -> SensitivityMetric {
+        self.metric
+    }
+
+    pub fn baseline(&self) -> f64 {
+        self.baseline
+    }
+
+    pub fn entries(&self) -> &Vec<SensitivityEntry> {
+        &self.entries
+    }
+
+    // Entries ordered from the most to the least valuable upgrade. Fewer
+    // rounds to kill is better, more damage per round is better.
+    pub fn ranked(&self) -> Vec<&SensitivityEntry> {
+        let mut entries = self.entries.iter().collect::<Vec<&SensitivityEntry>>();
+
+        entries.sort_by(|a, b| match self.metric {
+            SensitivityMetric::DamagePerRound => b.change.total_cmp(&a.change),
+            SensitivityMetric::TimeToKill(_) => a.change.total_cmp(&b.change),
+        });
+
+        entries
+    }
+}
+
+impl std::fmt::Display for SensitivityResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string_list = vec![
+            align_string(
+                &format!("BASELINE {}", self.metric),
+                format!("{:.2}", self.baseline),
+            ),
+            "".into(),
+        ];
+
+        for entry in self.ranked() {
+            string_list.push(align_string(
+                &entry.perturbation.to_string(),
+                format!(
+                    "{:.2} ({:+.2}, {:+.2} per unit)",
+                    entry.value,
+                    entry.change,
+                    entry.change_per_unit()
+                ),
+            ));
+        }
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Perturbation, SensitivityMetric};
+    use crate::{
+        character::{AbilityList, Character},
+        dice::Dice,
+        feat::feat_db::get_feat,
+        item::{weapon_db::get_weapon_base, Damage, DamageType, ItemProperty, Weapon},
+        simulator::CombatSimulator,
+        target::TargetProfile,
+    };
+
+    #[test]
+    fn sensitivity() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(20).build())
+            .ab(30)
+            .base_apr(4)
+            .weapon(Weapon::new("".into(), get_weapon_base("Scimitar"), vec![]))
+            .build();
+
+        let character = Perturbation::Strength(2).apply(&attacker);
+        assert_eq!(character.abilities.str.get_mod(), 6);
+
+        let character = Perturbation::ThreatRange(1).apply(&attacker);
+        assert_eq!(character.weapon_threat_range(), 17);
+
+        let character = Perturbation::CritMultiplier(1).apply(&character);
+        assert_eq!(character.weapon_crit_multiplier(), 3);
+        assert_eq!(character.weapon_threat_range(), 17);
+
+        let character = Perturbation::Feat(get_feat("Improved Critical")).apply(&attacker);
+        let character = Perturbation::Feat(get_feat("Improved Critical")).apply(&character);
+        assert_eq!(character.feats.len(), 1);
+
+        let damage = Damage::new(DamageType::Fire, Dice::from("1d6"), true, false);
+        let character = Perturbation::DamageBonus(damage.clone()).apply(&attacker);
+        assert!(character
+            .weapon
+            .item_properties
+            .contains(&ItemProperty::DamageBonus(damage.clone())));
+        assert_eq!(
+            Perturbation::DamageBonus(damage.clone()).to_string(),
+            "1d6 Fire"
+        );
+        assert_eq!(Perturbation::ExtraApr(1).units(), 1);
+
+        let simulator = CombatSimulator::new(2000);
+        let result = simulator.sensitivity(
+            &attacker,
+            &TargetProfile::new(45).to_character(),
+            &[
+                Perturbation::AttackBonus(5),
+                Perturbation::ExtraApr(1),
+                Perturbation::DamageBonus(damage),
+            ],
+            SensitivityMetric::DamagePerRound,
+        );
+
+        assert_eq!(result.entries().len(), 3);
+        assert!(result.baseline() > 0.0);
+        assert!(result.entries().iter().all(|x| x.change > 0.0));
+        assert!(
+            (result.entries()[0].change_per_unit() * 5.0 - result.entries()[0].change).abs() < 1e-9
+        );
+        assert!(result.ranked()[0].change >= result.ranked()[2].change);
+    }
+}
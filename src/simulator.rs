@@ -1,13 +1,29 @@
 use super::{
-    character::Character,
-    combat::{Combat, CombatStatistics},
-    feat::feat_db::get_feat,
+    activated_ability::{AbilityState, BurstResult},
+    breakpoint::{attack_breakpoints, crossovers, Breakpoint, BreakpointKind, BreakpointResult},
+    character::{Character, CharacterBuilder},
+    combat::{AttackContext, Combat, CombatStatistics, RoundState},
+    defense::{DefenderImprovement, DefenseResult},
+    dice::{set_seed, with_rng, with_saved_rng},
+    fight::{duel, encounter, rounds_to_kill, DuelResult, EncounterResult, TimeToKillResult},
+    item::{weapon_db::get_weapon_base_list, ItemProperty, Weapon},
+    optimizer::{optimize, BuildSpace, OptimizerResult},
+    rules::DEFAULT_PRECISION_BATCH_SIZE,
+    sensitivity::{Perturbation, SensitivityMetric, SensitivityResult},
+    statistics::RunningStatistics,
     string::align_string,
+    target::{ParameterSweep, TargetProfile},
 };
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::{cell::Cell, collections::HashMap};
+use std::{
+    cell::Cell,
+    cmp::{max, min},
+    collections::HashMap,
+    ops::RangeInclusive,
+};
 
-type CombatCallbackFn = dyn Fn(&Character, &i32, &CombatStatistics) -> ();
+type CombatCallbackFn = dyn Fn(&Character, &i32, &CombatStatistics);
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct DamageTestResult {
@@ -28,23 +44,20 @@ impl DamageTestResult {
         }
 
         let target = target.unwrap();
-        let mut string_list: Vec<String> = vec![];
-
-        string_list.push(align_string("TARGET AC", target_ac.to_string()));
-        string_list.push("".into());
-        string_list.push(target.to_string());
-        string_list.push("".into());
-        string_list.push(align_string(
-            "AVERAGE DAMAGE PER ROUND",
-            format!("{:.2}", target.dmg_dealt.total_dmg() / self.total_rounds),
-        ));
+        let string_list: Vec<String> = vec![
+            align_string("TARGET AC", target_ac.to_string()),
+            "".into(),
+            target.to_string(),
+            "".into(),
+            target.dmg_per_round_string(),
+        ];
 
         string_list.join("\n")
     }
 }
 
-impl ToString for DamageTestResult {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for DamageTestResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut string_list: Vec<String> = vec![];
 
         let mut ac_list = self.statistics.keys().collect::<Vec<&i32>>();
@@ -60,13 +73,193 @@ impl ToString for DamageTestResult {
             }
         }
 
-        string_list.join("\n")
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SweepEntry {
+    pub target: TargetProfile,
+    pub statistics: CombatStatistics,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct SweepResult {
+    total_rounds: i32,
+    entries: Vec<SweepEntry>,
+}
+
+impl SweepResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> &Vec<SweepEntry> {
+        &self.entries
+    }
+
+    pub fn get(&self, target: &TargetProfile) -> Option<&CombatStatistics> {
+        self.entries
+            .iter()
+            .find(|x| x.target == *target)
+            .map(|x| &x.statistics)
+    }
+
+    pub fn average_dmg_per_round(&self, target: &TargetProfile) -> Option<f64> {
+        self.get(target).map(|x| x.dmg_per_round.mean())
+    }
+}
+
+impl std::fmt::Display for SweepResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string_list: Vec<String> = vec![];
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            string_list.push(entry.target.to_string());
+            string_list.push("".into());
+            string_list.push(entry.statistics.to_string());
+            string_list.push("".into());
+            string_list.push(entry.statistics.dmg_per_round_string());
+            string_list.push("".into());
+
+            if i != self.entries.len() - 1 {
+                string_list.push("=".repeat(50));
+                string_list.push("".into());
+            }
+        }
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WeaponRankingEntry {
+    pub weapon: Weapon,
+    pub off_hand_weapon: Option<Weapon>,
+    // Threat range and multiplier after the character's feats.
+    pub threat_range: i32,
+    pub crit_multiplier: i32,
+    pub is_twohanded: bool,
+    pub statistics: CombatStatistics,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct WeaponRankingResult {
+    entries: Vec<WeaponRankingEntry>,
+    skipped_weapons: Vec<String>,
+}
+
+impl WeaponRankingResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Best weapon first.
+    pub fn entries(&self) -> &Vec<WeaponRankingEntry> {
+        &self.entries
+    }
+
+    // Names of the weapons the character is too small to wield, or to
+    // wield in the off hand when dual wielding, or that have no damage
+    // type in the weapon database.
+    pub fn skipped_weapons(&self) -> &Vec<String> {
+        &self.skipped_weapons
+    }
+}
+
+impl std::fmt::Display for WeaponRankingResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut string_list: Vec<String> = vec![];
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            string_list.push(align_string(
+                &format!("#{} {}", i + 1, entry.weapon.base.name),
+                format!(
+                    "{:.2} DPR, {}, {} x{}, {}",
+                    entry.statistics.dmg_per_round.mean(),
+                    entry.weapon.base.damage,
+                    if entry.threat_range < 20 {
+                        format!("{}-20", entry.threat_range)
+                    } else {
+                        "20".into()
+                    },
+                    entry.crit_multiplier,
+                    if entry.is_twohanded {
+                        "two-handed"
+                    } else {
+                        "one-handed"
+                    }
+                ),
+            ));
+        }
+
+        if !self.skipped_weapons.is_empty() {
+            string_list.push("".into());
+            string_list.push(align_string("SKIPPED", self.skipped_weapons.join(", ")));
+        }
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Tolerance {
+    // Maximum half-width of the 95% confidence interval of DPR.
+    Absolute(f64),
+    // Maximum half-width of the 95% confidence interval of DPR, as a
+    // fraction of the mean DPR.
+    Relative(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrecisionTarget {
+    pub tolerance: Tolerance,
+    pub batch_size: i32,
+}
+
+impl PrecisionTarget {
+    pub fn new(tolerance: Tolerance) -> Self {
+        Self {
+            tolerance,
+            batch_size: DEFAULT_PRECISION_BATCH_SIZE,
+        }
+    }
+
+    pub fn absolute(tolerance: f64) -> Self {
+        Self::new(Tolerance::Absolute(tolerance))
+    }
+
+    pub fn relative(tolerance: f64) -> Self {
+        Self::new(Tolerance::Relative(tolerance))
+    }
+
+    pub fn batch_size(mut self, batch_size: i32) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn is_met(&self, dmg_per_round: &RunningStatistics) -> bool {
+        if dmg_per_round.count() < 2 {
+            return false;
+        }
+
+        let half_width = dmg_per_round.confidence_interval_half_width();
+
+        match self.tolerance {
+            Tolerance::Absolute(tolerance) => half_width <= tolerance,
+            Tolerance::Relative(tolerance) => half_width <= tolerance * dmg_per_round.mean().abs(),
+        }
     }
 }
 
 #[derive(Default)]
 pub struct CombatSimulator<'a> {
     total_rounds: i32,
+    precision_target: Cell<Option<PrecisionTarget>>,
+    attack_context: Cell<AttackContext>,
+    // Seed every round's rolls are derived from while comparing runs, see
+    // `with_common_seed`.
+    common_seed: Cell<Option<u64>>,
     damage_test_notifier: Cell<Option<&'a CombatCallbackFn>>,
 }
 
@@ -74,23 +267,49 @@ impl<'a> CombatSimulator<'a> {
     pub fn new(total_rounds: i32) -> Self {
         Self {
             total_rounds,
+            precision_target: Cell::new(None),
+            attack_context: Cell::new(AttackContext::default()),
+            common_seed: Cell::new(None),
             damage_test_notifier: Cell::new(None),
         }
     }
 
+    // Simulates `total_rounds` rounds. If a precision target is set, rounds
+    // are simulated in batches until the target is met instead and
+    // `total_rounds` only acts as a hard cap. The number of rounds actually
+    // simulated is reported by `CombatStatistics::total_rounds`.
     pub fn begin(&self, attacker: &Character, defender: &Character) -> CombatStatistics {
         let mut statistics = CombatStatistics::new();
-        let combat = Combat::new(attacker, defender);
+        let combat = Combat::with_context(attacker, defender, self.attack_context.get());
+        let precision_target = self.precision_target.get();
+        let batch_size = max(
+            1,
+            precision_target
+                .map(|x| x.batch_size)
+                .unwrap_or(self.total_rounds),
+        );
+        let mut rounds = 0;
 
-        for _ in 1..=self.total_rounds {
-            let round_statistics = combat.resolve_round();
+        while rounds < self.total_rounds {
+            let batch_rounds = min(batch_size, self.total_rounds - rounds);
 
-            statistics.total_hits += round_statistics.total_hits;
-            statistics.total_misses += round_statistics.total_misses;
-            statistics.concealed_attacks += round_statistics.concealed_attacks;
-            statistics.epic_dodged_attacks += round_statistics.epic_dodged_attacks;
-            statistics.critical_hits += round_statistics.critical_hits;
-            statistics.dmg_dealt.add_from(&round_statistics.dmg_dealt);
+            for i in 0..batch_rounds {
+                if let Some(seed) = self.common_seed.get() {
+                    set_seed(seed ^ (rounds + i) as u64);
+                }
+
+                let round_statistics = combat.resolve_round();
+
+                statistics.add_from(&round_statistics);
+            }
+
+            rounds += batch_rounds;
+
+            if let Some(precision_target) = precision_target {
+                if precision_target.is_met(&statistics.dmg_per_round) {
+                    break;
+                }
+            }
         }
 
         statistics
@@ -104,36 +323,672 @@ impl<'a> CombatSimulator<'a> {
         target_physical_immunity: i32,
         target_defensive_essence: i32,
         target_has_epic_dodge: bool,
+    ) -> DamageTestResult {
+        let dummy = TargetProfile {
+            concealment: target_concealment,
+            physical_immunity: target_physical_immunity,
+            defensive_essence: target_defensive_essence,
+            has_epic_dodge: target_has_epic_dodge,
+            ..Default::default()
+        }
+        .to_character();
+
+        self.damage_test_with_defender(attacker, &dummy, target_ac_list)
+    }
+
+    // Same as `damage_test` but uses the given defender as a template and
+    // only overrides its AC for each entry in the target AC list.
+    pub fn damage_test_with_defender(
+        &self,
+        attacker: &Character,
+        defender: &Character,
+        target_ac_list: Vec<i32>,
     ) -> DamageTestResult {
         let mut result = DamageTestResult::new();
 
         for target_ac in target_ac_list {
-            let mut dummy = Character::builder()
-                .name("Combat Dummy".into())
+            let dummy = CharacterBuilder::from(defender.clone())
                 .ac(target_ac)
-                .concealment(target_concealment)
-                .physical_immunity(target_physical_immunity)
-                .defensive_essence(target_defensive_essence);
+                .build();
+            let combat_statistics = self.begin(attacker, &dummy);
 
-            if target_has_epic_dodge {
-                dummy = dummy.add_feat(get_feat("Epic Dodge"));
+            if let Some(f) = self.damage_test_notifier.get() {
+                f(attacker, &target_ac, &combat_statistics);
             }
 
-            let dummy = dummy.build();
+            result.statistics.insert(target_ac, combat_statistics);
+        }
+
+        result.total_rounds = self.total_rounds;
+        result
+    }
+
+    // Simulates the attacker against every target profile in the sweep
+    // and keeps the statistics keyed by the full profile.
+    pub fn sweep(&self, attacker: &Character, sweep: &ParameterSweep) -> SweepResult {
+        let mut result = SweepResult::new();
+
+        for target in sweep.profiles() {
+            let dummy = target.to_character();
             let combat_statistics = self.begin(attacker, &dummy);
 
             if let Some(f) = self.damage_test_notifier.get() {
-                f(&attacker, &target_ac, &combat_statistics);
+                f(attacker, &target.ac, &combat_statistics);
             }
 
-            result.statistics.insert(target_ac, combat_statistics);
+            result.entries.push(SweepEntry {
+                target,
+                statistics: combat_statistics,
+            });
         }
 
         result.total_rounds = self.total_rounds;
         result
     }
 
+    // Fights the defender until its hit points are depleted, `total_fights`
+    // times, and collects how many rounds each kill took. No fights are
+    // simulated if the defender has no hit points.
+    pub fn time_to_kill(
+        &self,
+        attacker: &Character,
+        defender: &Character,
+        total_fights: i32,
+    ) -> TimeToKillResult {
+        let mut result = TimeToKillResult::new();
+
+        if defender.hit_points <= 0 {
+            result.set_missing_hit_points();
+            return result;
+        }
+        let combat = Combat::with_context(attacker, defender, self.attack_context.get());
+
+        for fight in 1..=total_fights {
+            let fight_seed = self.common_seed.get().map(|x| x ^ ((fight as u64) << 32));
+
+            result.add(rounds_to_kill(&combat, fight_seed));
+        }
+
+        result
+    }
+
+    // Fights of `rounds` rounds each, as many as fit in `total_rounds`, with
+    // the attacker's activated abilities used as their policies allow.
+    pub fn burst(&self, attacker: &Character, defender: &Character, rounds: i32) -> BurstResult {
+        let mut result = BurstResult::new();
+        let combat = Combat::with_context(attacker, defender, self.attack_context.get());
+
+        for _ in 0..max(1, self.total_rounds / max(1, rounds)) {
+            let mut abilities = AbilityState::new(attacker, defender);
+            let round_dmg = (0..rounds)
+                .map(|_| {
+                    combat
+                        .resolve_fight_round(&mut RoundState::default(), &mut abilities)
+                        .dmg_dealt
+                        .total_dmg()
+                })
+                .collect::<Vec<i32>>();
+
+            result.add(&round_dmg);
+        }
+
+        result
+    }
+
+    // Lets both characters attack each other until one of them dies,
+    // `total_fights` times. No fights are simulated if either of them has no
+    // hit points.
+    pub fn duel(&self, first: &Character, second: &Character, total_fights: i32) -> DuelResult {
+        let mut result = DuelResult::new(first, second);
+
+        if first.hit_points <= 0 || second.hit_points <= 0 {
+            result.set_missing_hit_points();
+            return result;
+        }
+
+        for _ in 1..=total_fights {
+            result.add(duel(first, second));
+        }
+
+        result
+    }
+
+    // Fights the defender with every attacker at once until it dies,
+    // `total_fights` times. No fights are simulated if the defender has no
+    // hit points.
+    pub fn encounter(
+        &self,
+        attackers: &[Character],
+        defender: &Character,
+        total_fights: i32,
+    ) -> EncounterResult {
+        let mut result = EncounterResult::new(attackers);
+
+        if defender.hit_points <= 0 {
+            result.set_missing_hit_points();
+            return result;
+        }
+
+        for _ in 1..=total_fights {
+            let rounds = encounter(attackers, defender, &mut result);
+            result.add(rounds);
+        }
+
+        result
+    }
+
+    // Runs `f` with the rolls of every round derived from the seed and the
+    // round's index. Runs of different builds then see the same rolls round
+    // by round, even if they make a different number of rolls per round,
+    // which keeps small differences from drowning in noise. The random
+    // number generator is restored afterwards.
+    fn with_common_seed<T>(&self, seed: u64, f: impl FnOnce() -> T) -> T {
+        let previous_seed = self.common_seed.replace(Some(seed));
+        let result = with_saved_rng(f);
+
+        self.common_seed.set(previous_seed);
+        result
+    }
+
+    // Measures the build and every perturbation of it against the defender,
+    // with common random numbers.
+    pub fn sensitivity(
+        &self,
+        attacker: &Character,
+        defender: &Character,
+        perturbations: &[Perturbation],
+        metric: SensitivityMetric,
+    ) -> SensitivityResult {
+        let seed = with_rng(|rng| rng.gen::<u64>());
+        let measure = |character: &Character| {
+            self.with_common_seed(seed, || match metric {
+                SensitivityMetric::DamagePerRound => {
+                    self.begin(character, defender).dmg_per_round.mean()
+                }
+                SensitivityMetric::TimeToKill(total_fights) => self
+                    .time_to_kill(character, defender, total_fights)
+                    .mean_rounds(),
+            })
+        };
+
+        let mut result = SensitivityResult::new(metric, measure(attacker));
+
+        for perturbation in perturbations {
+            result.add(perturbation.clone(), measure(&perturbation.apply(attacker)));
+        }
+
+        result
+    }
+
+    // Searches the build space for the builds with the highest DPR against
+    // the defender and returns the best `top_n` of them. Every build is
+    // simulated with common random numbers.
+    pub fn optimize(
+        &self,
+        template: &Character,
+        defender: &Character,
+        space: &BuildSpace,
+        top_n: usize,
+    ) -> OptimizerResult {
+        let seed = with_rng(|rng| rng.gen::<u64>());
+
+        optimize(template, space, top_n, |character| {
+            self.with_common_seed(seed, || {
+                self.begin(character, defender).dmg_per_round.mean()
+            })
+        })
+    }
+
+    // Swaps every known weapon base with the given item properties into the
+    // template and ranks them by DPR against the defender. If the template
+    // dual wields, both hands wield the weapon base. Weapons the
+    // character can't wield that way or that have no damage type are
+    // skipped.
+    pub fn rank_weapons(
+        &self,
+        template: &Character,
+        item_properties: Vec<ItemProperty>,
+        defender: &Character,
+    ) -> WeaponRankingResult {
+        let seed = with_rng(|rng| rng.gen::<u64>());
+        let mut result = WeaponRankingResult::new();
+        let mut weapon_bases = get_weapon_base_list().into_values().collect::<Vec<_>>();
+        weapon_bases.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let is_dual_wielding = template.is_dual_wielding();
+
+        for weapon_base in weapon_bases {
+            // Weapons without a damage type can't be resolved in combat.
+            if !template.can_wield(&weapon_base)
+                || weapon_base.damage_type.is_empty()
+                || (is_dual_wielding && weapon_base.size > template.size)
+            {
+                result.skipped_weapons.push(weapon_base.name);
+                continue;
+            }
+
+            let weapon = Weapon::new(
+                weapon_base.name.clone(),
+                weapon_base,
+                item_properties.clone(),
+            );
+            let mut character = CharacterBuilder::from(template.clone()).weapon(weapon.clone());
+
+            if is_dual_wielding {
+                character = character.off_hand_weapon(weapon);
+            }
+
+            let character = character.build();
+
+            let statistics = self.with_common_seed(seed, || self.begin(&character, defender));
+
+            result.entries.push(WeaponRankingEntry {
+                threat_range: character.weapon_threat_range(),
+                crit_multiplier: character.weapon_crit_multiplier(),
+                is_twohanded: character.is_weapon_twohanded(),
+                weapon: character.weapon,
+                off_hand_weapon: character.off_hand_weapon,
+                statistics,
+            });
+        }
+
+        result.entries.sort_by(|a, b| {
+            b.statistics
+                .dmg_per_round
+                .mean()
+                .total_cmp(&a.statistics.dmg_per_round.mean())
+        });
+
+        result
+    }
+
+    // Finds the ACs within the range at which the attacker's behaviour
+    // changes, along with the AC where the competitor, if any, overtakes it
+    // or falls behind. The defender is used as a template with its AC
+    // overridden. ACs are before the attack context applies, the same as
+    // the AC the template is given.
+    pub fn breakpoints(
+        &self,
+        attacker: &Character,
+        defender: &Character,
+        ac_range: RangeInclusive<i32>,
+        competitor: Option<&Character>,
+    ) -> BreakpointResult {
+        let seed = with_rng(|rng| rng.gen::<u64>());
+        let dmg_per_round = |character: &Character, ac: i32| {
+            let dummy = CharacterBuilder::from(defender.clone()).ac(ac).build();
+
+            self.with_common_seed(seed, || self.begin(character, &dummy).dmg_per_round.mean())
+        };
+        let mut result = BreakpointResult::new();
+
+        let attack_context = self.attack_context.get();
+        let combat = Combat::with_context(attacker, defender, attack_context);
+        // Attacks are rolled against the AC in the attack context, which is
+        // off by the same amount for every AC the template is given.
+        let context_shift = defender.effective_ac(&attack_context)
+            - defender.effective_ac(&AttackContext::default());
+
+        for (kind, threshold) in attack_breakpoints(&combat) {
+            let ac = threshold - context_shift;
+
+            if !ac_range.contains(&ac) {
+                continue;
+            }
+
+            result.add(Breakpoint {
+                kind,
+                ac,
+                dmg_per_round_before: dmg_per_round(attacker, ac - 1),
+                dmg_per_round_after: dmg_per_round(attacker, ac),
+            });
+        }
+
+        if let Some(competitor) = competitor {
+            let table = ac_range
+                .map(|ac| {
+                    (
+                        ac,
+                        dmg_per_round(attacker, ac),
+                        dmg_per_round(competitor, ac),
+                    )
+                })
+                .collect::<Vec<(i32, f64, f64)>>();
+
+            for i in crossovers(&table) {
+                result.add(Breakpoint {
+                    kind: BreakpointKind::Crossover {
+                        competitor_ahead: table[i].2 > table[i].1,
+                        competitor_before: table[i - 1].2,
+                        competitor_after: table[i].2,
+                    },
+                    ac: table[i].0,
+                    dmg_per_round_before: table[i - 1].1,
+                    dmg_per_round_after: table[i].1,
+                });
+            }
+        }
+
+        result
+    }
+
+    // Measures the damage the defender takes per round from every attacker
+    // of the pool, and how much each improvement of the defender reduces it.
+    // Every attacker sees the same rolls against every version of the
+    // defender.
+    pub fn defense(
+        &self,
+        defender: &Character,
+        attackers: &[Character],
+        improvements: &[DefenderImprovement],
+    ) -> DefenseResult {
+        let improved_defenders = improvements
+            .iter()
+            .map(|x| x.apply(defender))
+            .collect::<Vec<Character>>();
+        let mut dmg_taken = vec![0.0; improvements.len()];
+        let mut result = DefenseResult::new();
+
+        for attacker in attackers {
+            let seed = with_rng(|rng| rng.gen::<u64>());
+
+            result.add_attacker(
+                attacker.name.clone(),
+                self.with_common_seed(seed, || self.begin(attacker, defender))
+                    .dmg_per_round
+                    .mean(),
+            );
+
+            for (i, improved_defender) in improved_defenders.iter().enumerate() {
+                dmg_taken[i] += self
+                    .with_common_seed(seed, || self.begin(attacker, improved_defender))
+                    .dmg_per_round
+                    .mean();
+            }
+        }
+
+        for (improvement, dmg_taken) in improvements.iter().zip(dmg_taken) {
+            result.add(*improvement, dmg_taken / max(1, attackers.len()) as f64);
+        }
+
+        result
+    }
+
+    pub fn set_precision_target(&self, precision_target: PrecisionTarget) {
+        self.precision_target.set(Some(precision_target));
+    }
+
+    // Context of every attack made by `begin` and `time_to_kill`, and the
+    // analyses built on them.
+    pub fn set_attack_context(&self, attack_context: AttackContext) {
+        self.attack_context.set(attack_context);
+    }
+
     pub fn set_damage_test_notifier(&self, f: &'a CombatCallbackFn) {
         self.damage_test_notifier.set(Some(f));
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        character::{AbilityList, Character},
+        dice::{set_seed, Dice},
+        feat::feat_db::get_feat,
+        item::{weapon_db::get_weapon_base, ItemProperty, Weapon},
+        simulator::{CombatSimulator, PrecisionTarget},
+        size::SizeCategory,
+        target::{ParameterSweep, TargetProfile},
+    };
+
+    #[test]
+    fn sweep() {
+        let attacker = Character::builder()
+            .ab(30)
+            .base_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let simulator = CombatSimulator::new(100);
+
+        let result = simulator.sweep(
+            &attacker,
+            &ParameterSweep::new()
+                .ac(vec![10, 60])
+                .concealment(vec![0, 100]),
+        );
+
+        assert_eq!(result.entries().len(), 4);
+
+        let statistics = result.get(&TargetProfile::new(10)).unwrap();
+        assert_eq!(statistics.total_attacks(), 100);
+        // Only a natural 1 misses.
+        assert!(statistics.total_hits >= 80);
+
+        let statistics = result
+            .get(&TargetProfile {
+                ac: 10,
+                concealment: 100,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(statistics.concealed_attacks >= 90);
+
+        assert!(result.get(&TargetProfile::new(20)).is_none());
+    }
+
+    #[test]
+    fn damage_test_with_defender() {
+        let attacker = Character::builder()
+            .ab(30)
+            .base_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let defender = Character::builder()
+            .feats(vec![get_feat("Critical Immunity")])
+            .physical_damage_reduction(100)
+            .build();
+        let simulator = CombatSimulator::new(100);
+
+        let result = simulator.damage_test_with_defender(&attacker, &defender, vec![10, 20]);
+
+        for ac in [10, 20] {
+            let statistics = result.statistics.get(&ac).unwrap();
+            assert_eq!(statistics.total_attacks(), 100);
+            assert_eq!(statistics.critical_hits, 0);
+            assert_eq!(statistics.dmg_dealt.total_dmg(), 0);
+        }
+    }
+
+    #[test]
+    fn time_to_kill() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(100)
+            .base_apr(1)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let defender = Character::builder()
+            .feats(vec![get_feat("Critical Immunity")])
+            .hit_points(8)
+            .build();
+        let simulator = CombatSimulator::new(0);
+
+        let result = simulator.time_to_kill(&attacker, &defender, 100);
+
+        assert_eq!(result.total_fights(), 100);
+        assert_eq!(result.unresolved_fights(), 0);
+        assert_eq!(result.percentile(0.0).map(|x| x >= 1), Some(true));
+        assert!(result.mean_rounds() <= 8.0 / 0.95);
+
+        let defender = Character::builder()
+            .hit_points(1)
+            .physical_damage_reduction(100)
+            .build();
+
+        let result = simulator.time_to_kill(&attacker, &defender, 1);
+
+        assert_eq!(result.unresolved_fights(), 1);
+
+        // Dummies have no hit points unless they are set.
+        let dummy = TargetProfile::new(10).to_character();
+        let result = simulator.time_to_kill(&attacker, &dummy, 10);
+        assert!(result.missing_hit_points());
+        assert_eq!(result.total_fights(), 0);
+        assert!(result.to_string().contains("NO HIT POINTS"));
+        assert!(simulator
+            .encounter(std::slice::from_ref(&attacker), &dummy, 10)
+            .time_to_kill()
+            .missing_hit_points());
+        assert_eq!(simulator.duel(&attacker, &dummy, 10).total_fights(), 0);
+
+        let dummy = TargetProfile {
+            hit_points: 8,
+            ..TargetProfile::new(10)
+        }
+        .to_character();
+        let result = simulator.time_to_kill(&attacker, &dummy, 10);
+        assert!(!result.missing_hit_points());
+        assert_eq!(result.total_fights(), 10);
+    }
+
+    #[test]
+    fn common_seed() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(30)
+            .base_apr(2)
+            .weapon(Weapon::new("".into(), get_weapon_base("Scimitar"), vec![]))
+            .build();
+        let extra_attacker = Character {
+            extra_apr: 1,
+            ..attacker.clone()
+        };
+        let defender = TargetProfile {
+            ac: 40,
+            concealment: 50,
+            ..Default::default()
+        }
+        .to_character();
+        let simulator = CombatSimulator::new(200);
+
+        let (statistics, extra_statistics) = simulator.with_common_seed(42, || {
+            (
+                simulator.begin(&attacker, &defender),
+                simulator.begin(&extra_attacker, &defender),
+            )
+        });
+
+        // The extra attack comes last in the round, so it doesn't shift the
+        // rolls of the attacks before it in later rounds.
+        for (slot, extra_slot) in statistics
+            .attack_slots
+            .iter()
+            .zip(&extra_statistics.attack_slots)
+        {
+            assert_eq!(slot.total_hits, extra_slot.total_hits);
+            assert_eq!(slot.dmg_dealt, extra_slot.dmg_dealt);
+        }
+        assert_eq!(extra_statistics.attack_slots.len(), 3);
+
+        // Rolls made afterwards continue from where they were before.
+        let d1000 = Dice::from("1d1000");
+        set_seed(7);
+        simulator.with_common_seed(42, || simulator.begin(&attacker, &defender));
+        let rolls = (0..5).map(|_| d1000.roll()).collect::<Vec<i32>>();
+        set_seed(7);
+        assert_eq!(rolls, (0..5).map(|_| d1000.roll()).collect::<Vec<i32>>());
+    }
+
+    #[test]
+    fn precision_target() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(10).build())
+            .ab(30)
+            .base_apr(4)
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let defender = Character::builder().ac(40).build();
+
+        let simulator = CombatSimulator::new(100_000);
+        simulator.set_precision_target(PrecisionTarget::absolute(0.5).batch_size(100));
+
+        let statistics = simulator.begin(&attacker, &defender);
+        assert!(statistics.total_rounds() < 100_000);
+        assert_eq!(statistics.total_rounds() % 100, 0);
+        assert!(statistics.dmg_per_round.confidence_interval_half_width() <= 0.5);
+
+        // Precision target can't be met, so it stops at the hard cap.
+        let simulator = CombatSimulator::new(250);
+        simulator.set_precision_target(PrecisionTarget::relative(0.0).batch_size(100));
+
+        let statistics = simulator.begin(&attacker, &defender);
+        assert_eq!(statistics.total_rounds(), 250);
+    }
+
+    #[test]
+    fn rank_weapons() {
+        let attacker = Character::builder()
+            .abilities(AbilityList::builder().str(20).build())
+            .size(SizeCategory::Small)
+            .ab(40)
+            .base_apr(1)
+            .feats(vec![get_feat("Improved Critical")])
+            .build();
+        let simulator = CombatSimulator::new(20);
+
+        let result = simulator.rank_weapons(
+            &attacker,
+            vec![ItemProperty::Keen],
+            &TargetProfile::new(20).to_character(),
+        );
+
+        assert!(result.skipped_weapons().contains(&"Greatsword".to_string()));
+        assert!(result
+            .entries()
+            .iter()
+            .all(|x| x.weapon.base.name != "Greatsword"));
+
+        let longsword = result
+            .entries()
+            .iter()
+            .find(|x| x.weapon.base.name == "Longsword")
+            .unwrap();
+        assert!(longsword.is_twohanded);
+        assert_eq!(longsword.threat_range, 15);
+        assert_eq!(longsword.crit_multiplier, 2);
+
+        let dagger = result
+            .entries()
+            .iter()
+            .find(|x| x.weapon.base.name == "Dagger")
+            .unwrap();
+        assert!(!dagger.is_twohanded);
+        assert!(dagger.off_hand_weapon.is_none());
+
+        let dual_wielder = Character::builder()
+            .abilities(AbilityList::builder().str(20).build())
+            .size(SizeCategory::Small)
+            .ab(40)
+            .base_apr(1)
+            .feats(vec![get_feat("Dual Wielding")])
+            .build();
+        let result = simulator.rank_weapons(
+            &dual_wielder,
+            vec![],
+            &TargetProfile::new(20).to_character(),
+        );
+
+        // Too large for the off hand of a small character, even without an
+        // off-hand weapon in the template.
+        assert!(result.skipped_weapons().contains(&"Longsword".to_string()));
+        assert!(result.entries().iter().all(|x| !x.is_twohanded));
+        assert!(result.entries().iter().all(|x| x
+            .off_hand_weapon
+            .as_ref()
+            .is_some_and(|off_hand_weapon| off_hand_weapon.base.name == x.weapon.base.name)));
+
+        assert!(result.entries().windows(2).all(|x| {
+            x[0].statistics.dmg_per_round.mean() >= x[1].statistics.dmg_per_round.mean()
+        }));
+    }
+}
@@ -1,18 +1,38 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum SizeCategory {
     Tiny,
     Small,
+    #[default]
     Medium,
     Large,
     Huge,
     Unknown,
 }
 
-impl Default for SizeCategory {
-    fn default() -> Self {
-        Self::Medium
+impl SizeCategory {
+    // Creatures smaller than medium are easier to hit with, larger ones
+    // are harder to hit with.
+    pub fn ab_modifier(&self) -> i32 {
+        match self {
+            Self::Tiny => 2,
+            Self::Small => 1,
+            Self::Medium | Self::Unknown => 0,
+            Self::Large => -1,
+            Self::Huge => -2,
+        }
+    }
+
+    // The next size category, or `Unknown` past the largest one.
+    pub fn larger(&self) -> Self {
+        match self {
+            Self::Tiny => Self::Small,
+            Self::Small => Self::Medium,
+            Self::Medium => Self::Large,
+            Self::Large => Self::Huge,
+            Self::Huge | Self::Unknown => Self::Unknown,
+        }
     }
 }
 
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+
+// z-score of the two-sided 95% confidence interval of a normal distribution.
+const Z_95: f64 = 1.96;
+
+// Tracks mean and variance of a sample online with Welford's algorithm,
+// so individual samples don't have to be kept around.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunningStatistics {
+    count: i64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl RunningStatistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+
+        self.count += 1;
+
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    // Combines two sets of samples as if every sample was added to a single
+    // one (Chan et al. parallel algorithm).
+    pub fn merge(&mut self, other: &RunningStatistics) {
+        if other.count == 0 {
+            return;
+        }
+
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count * other.count) as f64 / count as f64;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.count = count;
+    }
+
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    // Unbiased sample variance.
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            return 0.0;
+        }
+
+        self.m2 / (self.count - 1) as f64
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    pub fn std_error(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+
+        self.std_dev() / (self.count as f64).sqrt()
+    }
+
+    // Half-width of the 95% confidence interval of the mean.
+    pub fn confidence_interval_half_width(&self) -> f64 {
+        Z_95 * self.std_error()
+    }
+
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        let half_width = self.confidence_interval_half_width();
+
+        (self.mean - half_width, self.mean + half_width)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RunningStatistics;
+
+    #[test]
+    fn running_statistics() {
+        let mut statistics = RunningStatistics::new();
+        assert_eq!(statistics.count(), 0);
+        assert_eq!(statistics.variance(), 0.0);
+        assert_eq!(statistics.std_error(), 0.0);
+
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            statistics.add(value);
+        }
+
+        assert_eq!(statistics.count(), 8);
+        assert_eq!(statistics.mean(), 5.0);
+        assert_eq!(statistics.min(), 2.0);
+        assert_eq!(statistics.max(), 9.0);
+        assert!((statistics.variance() - 32.0 / 7.0).abs() < 1e-9);
+        assert!((statistics.std_error() - (32.0f64 / 7.0 / 8.0).sqrt()).abs() < 1e-9);
+
+        let (low, high) = statistics.confidence_interval();
+        assert!(
+            (statistics.mean() - low - statistics.confidence_interval_half_width()).abs() < 1e-9
+        );
+        assert!(
+            (high - statistics.mean() - statistics.confidence_interval_half_width()).abs() < 1e-9
+        );
+
+        let mut first = RunningStatistics::new();
+        let mut second = RunningStatistics::new();
+
+        for value in [2.0, 4.0, 4.0] {
+            first.add(value);
+        }
+
+        for value in [4.0, 5.0, 5.0, 7.0, 9.0] {
+            second.add(value);
+        }
+
+        first.merge(&second);
+
+        assert_eq!(first.count(), statistics.count());
+        assert!((first.mean() - statistics.mean()).abs() < 1e-9);
+        assert!((first.variance() - statistics.variance()).abs() < 1e-9);
+        assert_eq!(first.min(), 2.0);
+        assert_eq!(first.max(), 9.0);
+
+        let mut empty = RunningStatistics::new();
+        empty.merge(&statistics);
+        assert_eq!(empty, statistics);
+    }
+}
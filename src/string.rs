@@ -1,6 +1,10 @@
 pub fn align_string(left: &str, right: String) -> String {
     let width = 32;
-    let space_count = width - left.len();
+    let space_count = if left.len() < width {
+        width - left.len()
+    } else {
+        1
+    };
 
     format!("{}{}{}", left, " ".repeat(space_count), right)
 }
@@ -0,0 +1,239 @@
+use super::{character::Character, feat::feat_db::get_feat, race::RacialType};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Copy, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct TargetProfile {
+    pub ac: i32,
+    pub concealment: i32,
+    pub physical_immunity: i32,
+    pub physical_damage_reduction: i32,
+    pub defensive_essence: i32,
+    pub has_epic_dodge: bool,
+    #[serde(default)]
+    pub race: RacialType,
+    // Only used by fights, which can't be simulated against a defender
+    // without hit points.
+    #[serde(default)]
+    pub hit_points: i32,
+}
+
+impl TargetProfile {
+    pub fn new(ac: i32) -> Self {
+        Self {
+            ac,
+            ..Default::default()
+        }
+    }
+
+    pub fn to_character(&self) -> Character {
+        let mut dummy = Character::builder()
+            .name("Combat Dummy".into())
+            .ac(self.ac)
+            .concealment(self.concealment)
+            .physical_immunity(self.physical_immunity)
+            .physical_damage_reduction(self.physical_damage_reduction)
+            .defensive_essence(self.defensive_essence)
+            .race(self.race)
+            .hit_points(self.hit_points);
+
+        if self.has_epic_dodge {
+            dummy = dummy.add_feat(get_feat("Epic Dodge"));
+        }
+
+        dummy.build()
+    }
+}
+
+impl std::fmt::Display for TargetProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AC {} / {}% CONCEALMENT / {}% IMMUNITY / {} DR / {} DEF. ESSENCE / {}{}{}",
+            self.ac,
+            self.concealment,
+            self.physical_immunity,
+            self.physical_damage_reduction,
+            self.defensive_essence,
+            self.race.to_string().to_uppercase(),
+            if self.has_epic_dodge {
+                " / EPIC DODGE"
+            } else {
+                ""
+            },
+            if self.hit_points > 0 {
+                format!(" / {} HP", self.hit_points)
+            } else {
+                "".into()
+            }
+        )
+    }
+}
+
+// Describes the values each defender parameter takes in a sweep. Every
+// dimension that isn't set stays at a single default value, so the
+// resulting grid is the cartesian product of the dimensions that are.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParameterSweep {
+    pub ac: Vec<i32>,
+    pub concealment: Vec<i32>,
+    pub physical_immunity: Vec<i32>,
+    pub physical_damage_reduction: Vec<i32>,
+    pub defensive_essence: Vec<i32>,
+    pub epic_dodge: Vec<bool>,
+    #[serde(default = "default_races")]
+    pub race: Vec<RacialType>,
+}
+
+fn default_races() -> Vec<RacialType> {
+    vec![RacialType::default()]
+}
+
+impl Default for ParameterSweep {
+    fn default() -> Self {
+        Self {
+            ac: vec![0],
+            concealment: vec![0],
+            physical_immunity: vec![0],
+            physical_damage_reduction: vec![0],
+            defensive_essence: vec![0],
+            epic_dodge: vec![false],
+            race: default_races(),
+        }
+    }
+}
+
+impl ParameterSweep {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ac(mut self, values: impl IntoIterator<Item = i32>) -> Self {
+        self.ac = values.into_iter().collect();
+        self
+    }
+
+    pub fn concealment(mut self, values: impl IntoIterator<Item = i32>) -> Self {
+        self.concealment = values.into_iter().collect();
+        self
+    }
+
+    pub fn physical_immunity(mut self, values: impl IntoIterator<Item = i32>) -> Self {
+        self.physical_immunity = values.into_iter().collect();
+        self
+    }
+
+    pub fn physical_damage_reduction(mut self, values: impl IntoIterator<Item = i32>) -> Self {
+        self.physical_damage_reduction = values.into_iter().collect();
+        self
+    }
+
+    pub fn defensive_essence(mut self, values: impl IntoIterator<Item = i32>) -> Self {
+        self.defensive_essence = values.into_iter().collect();
+        self
+    }
+
+    pub fn epic_dodge(mut self, values: impl IntoIterator<Item = bool>) -> Self {
+        self.epic_dodge = values.into_iter().collect();
+        self
+    }
+
+    pub fn race(mut self, values: impl IntoIterator<Item = RacialType>) -> Self {
+        self.race = values.into_iter().collect();
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.ac.len()
+            * self.concealment.len()
+            * self.physical_immunity.len()
+            * self.physical_damage_reduction.len()
+            * self.defensive_essence.len()
+            * self.epic_dodge.len()
+            * self.race.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn profiles(&self) -> Vec<TargetProfile> {
+        let mut profiles = Vec::with_capacity(self.len());
+
+        for &ac in &self.ac {
+            for &concealment in &self.concealment {
+                for &physical_immunity in &self.physical_immunity {
+                    for &physical_damage_reduction in &self.physical_damage_reduction {
+                        for &defensive_essence in &self.defensive_essence {
+                            for &has_epic_dodge in &self.epic_dodge {
+                                for &race in &self.race {
+                                    profiles.push(TargetProfile {
+                                        ac,
+                                        concealment,
+                                        physical_immunity,
+                                        physical_damage_reduction,
+                                        defensive_essence,
+                                        has_epic_dodge,
+                                        race,
+                                        ..Default::default()
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        profiles
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ParameterSweep, TargetProfile};
+    use crate::{combat::AttackContext, race::RacialType};
+
+    #[test]
+    fn parameter_sweep() {
+        let sweep = ParameterSweep::new()
+            .ac((40..=50).step_by(5))
+            .concealment(vec![0, 50])
+            .epic_dodge(vec![false, true]);
+
+        assert_eq!(sweep.len(), 12);
+
+        let profiles = sweep.profiles();
+        assert_eq!(profiles.len(), 12);
+        assert_eq!(
+            profiles[0],
+            TargetProfile {
+                ac: 40,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            profiles[11],
+            TargetProfile {
+                ac: 50,
+                concealment: 50,
+                has_epic_dodge: true,
+                ..Default::default()
+            }
+        );
+
+        let dummy = profiles[11].to_character();
+        assert_eq!(dummy.effective_ac(&AttackContext::default()), 50);
+        assert_eq!(dummy.concealment, 50);
+        assert!(dummy.has_epic_dodge());
+
+        assert!(ParameterSweep::new().ac(vec![]).is_empty());
+
+        let profiles = ParameterSweep::new()
+            .race(vec![RacialType::Human, RacialType::Orc])
+            .profiles();
+        assert_eq!(profiles[1].to_character().race, RacialType::Orc);
+        assert!(profiles[1].to_string().ends_with("/ ORC"));
+    }
+}
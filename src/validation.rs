@@ -0,0 +1,361 @@
+use super::{
+    character::Character,
+    class::Class,
+    feat::{
+        feat_db::{get_feat, get_feat_prerequisites, is_weapon_feat},
+        Feat, FeatPrerequisite,
+    },
+    string::align_string,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    // The build can't exist in game.
+    Error,
+    // The build is valid but part of it has no effect.
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "ERROR"),
+            Self::Warning => write!(f, "WARNING"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct ValidationResult {
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.issues.push(ValidationIssue {
+            severity: Severity::Error,
+            message,
+        });
+    }
+
+    pub fn warning(&mut self, message: String) {
+        self.issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            message,
+        });
+    }
+
+    pub fn issues(&self) -> &Vec<ValidationIssue> {
+        &self.issues
+    }
+
+    pub fn errors(&self) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|x| x.severity == Severity::Error)
+            .collect()
+    }
+
+    pub fn warnings(&self) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|x| x.severity == Severity::Warning)
+            .collect()
+    }
+
+    // Whether the build can be simulated, warnings are allowed.
+    pub fn is_valid(&self) -> bool {
+        self.errors().is_empty()
+    }
+}
+
+impl std::fmt::Display for ValidationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string_list = self
+            .issues
+            .iter()
+            .map(|x| align_string(&x.severity.to_string(), x.message.clone()))
+            .collect::<Vec<String>>();
+
+        write!(f, "{}", string_list.join("\n"))
+    }
+}
+
+fn is_class_prerequisite(prerequisite: &FeatPrerequisite) -> bool {
+    matches!(
+        prerequisite,
+        FeatPrerequisite::BaseAttackBonus(_)
+            | FeatPrerequisite::CharacterLevel(_)
+            | FeatPrerequisite::ClassLevel(..)
+    )
+}
+
+fn meets_prerequisite(character: &Character, feat: &Feat, prerequisite: &FeatPrerequisite) -> bool {
+    let class_levels = &character.class_levels;
+
+    match prerequisite {
+        // A weapon feat taken for every weapon stands in for the one of the
+        // wielded weapon.
+        FeatPrerequisite::Feat(name) if is_weapon_feat(name) => match feat.weapon_base() {
+            Some(weapon_base) => character.has_weapon_feat_for(name, weapon_base),
+            None => character.has_weapon_feat(name),
+        },
+        FeatPrerequisite::Feat(name) => character.has_feat(get_feat(name)),
+        FeatPrerequisite::Ability(ability, value) => {
+            character.abilities.get(*ability).value() >= *value
+        }
+        FeatPrerequisite::BaseAttackBonus(value) => class_levels.base_attack_bonus() >= *value,
+        FeatPrerequisite::CharacterLevel(level) => class_levels.total_level() >= *level,
+        FeatPrerequisite::ClassLevel(class, level) => class_levels.level(*class) >= *level,
+    }
+}
+
+fn validate_feats(character: &Character, result: &mut ValidationResult) {
+    // Characters built from a flat AB have no levels to check against.
+    let check_class = !character.class_levels.0.is_empty();
+    let mut skipped_class = false;
+
+    for feat in &character.feats {
+        let prerequisites = match get_feat_prerequisites(feat) {
+            Some(prerequisites) => prerequisites,
+            None => {
+                result.error(format!("Unknown feat {}", feat));
+                continue;
+            }
+        };
+
+        if feat.weapon_base().is_some() && !is_weapon_feat(feat.base_name()) {
+            result.error(format!("{} can't be taken for a weapon", feat.base_name()));
+            continue;
+        }
+
+        for prerequisite in &prerequisites {
+            if is_class_prerequisite(prerequisite) && !check_class {
+                skipped_class = true;
+                continue;
+            }
+
+            if !meets_prerequisite(character, feat, prerequisite) {
+                result.error(format!("{} requires {}", feat, prerequisite));
+            }
+        }
+
+        if let Some(weapon_base) = feat.weapon_base() {
+            let wielded = std::iter::once(&character.weapon)
+                .chain(character.off_hand_weapon.as_ref())
+                .any(|x| x.base.name == weapon_base);
+
+            if !wielded {
+                result.warning(format!("{} has no effect on the wielded weapons", feat));
+            }
+        }
+    }
+
+    if skipped_class {
+        result.warning("Class levels aren't set, class prerequisites weren't checked".into());
+    }
+}
+
+fn validate_weapons(character: &Character, result: &mut ValidationResult) {
+    if !character.can_wield(&character.weapon.base) {
+        result.error(format!(
+            "{} is too large to wield",
+            character.weapon.base.name
+        ));
+    }
+
+    if character.is_dual_wielding() && character.is_weapon_twohanded() {
+        result.error(format!(
+            "Dual Wielding isn't possible with a two-handed {}",
+            character.weapon.base.name
+        ));
+    }
+
+    if let Some(off_hand_weapon) = &character.off_hand_weapon {
+        if off_hand_weapon.base.size > character.size {
+            result.error(format!(
+                "{} is too large to wield in the off-hand",
+                off_hand_weapon.base.name
+            ));
+        }
+
+        if !character.is_dual_wielding() {
+            result.warning(format!(
+                "{} is unused without Dual Wielding",
+                off_hand_weapon.base.name
+            ));
+        }
+    }
+}
+
+// Rangers choose a favored enemy at level 1 and another every 5 levels.
+fn validate_favored_enemies(character: &Character, result: &mut ValidationResult) {
+    let favored_enemies = character.favored_enemies.len() as i32;
+
+    if character.has_bane_of_enemies() && favored_enemies == 0 {
+        result.warning("Bane of Enemies has no effect without favored enemies".into());
+    }
+
+    if favored_enemies == 0 || character.class_levels.0.is_empty() {
+        return;
+    }
+
+    let ranger_level = character.class_levels.level(Class::Ranger);
+    let allowed = if ranger_level > 0 {
+        1 + ranger_level / 5
+    } else {
+        0
+    };
+
+    if favored_enemies > allowed {
+        result.error(format!(
+            "{} Ranger levels allow {} favored enemies, not {}",
+            ranger_level, allowed, favored_enemies
+        ));
+    }
+}
+
+// Checks that the character is a build that can exist in game: feat
+// prerequisites, ability requirements and weapons its size can wield.
+pub fn validate(character: &Character) -> ValidationResult {
+    let mut result = ValidationResult::new();
+
+    validate_feats(character, &mut result);
+    validate_weapons(character, &mut result);
+    validate_favored_enemies(character, &mut result);
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::validate;
+    use crate::{
+        character::{AbilityList, Character},
+        class::{Class, ClassLevels},
+        feat::feat_db::{get_feat, get_weapon_feat},
+        item::{weapon_db::get_weapon_base, Weapon},
+        race::RacialType,
+        size::SizeCategory,
+    };
+
+    #[test]
+    fn validation() {
+        let character = Character::builder()
+            .abilities(AbilityList::builder().str(24).build())
+            .class_levels(ClassLevels(vec![
+                (Class::Fighter, 21),
+                (Class::WeaponMaster, 5),
+            ]))
+            .feats(vec![
+                get_weapon_feat("Weapon Focus", "Scimitar"),
+                get_weapon_feat("Weapon Specialization", "Scimitar"),
+                get_weapon_feat("Epic Weapon Focus", "Scimitar"),
+                get_weapon_feat("Epic Weapon Specialization", "Scimitar"),
+                get_feat("Improved Critical"),
+                get_feat("Overwhelming Critical"),
+                get_feat("Dual Wielding"),
+            ])
+            .weapon(Weapon::new("".into(), get_weapon_base("Scimitar"), vec![]))
+            .build();
+        let result = validate(&character);
+        assert!(result.issues().is_empty(), "{}", result);
+
+        // Missing Weapon Specialization and Ki Critical without levels.
+        let character = Character::builder()
+            .class_levels(ClassLevels(vec![(Class::Fighter, 21)]))
+            .feats(vec![
+                get_feat("Weapon Focus"),
+                get_feat("Epic Weapon Focus"),
+                get_feat("Epic Weapon Specialization"),
+                get_feat("Ki Critical"),
+                get_weapon_feat("Weapon Specialization", "Longsword"),
+            ])
+            .weapon(Weapon::new("".into(), get_weapon_base("Scimitar"), vec![]))
+            .build();
+        let result = validate(&character);
+        assert_eq!(result.errors().len(), 2, "{}", result);
+        assert!(result.errors()[0]
+            .message
+            .contains("requires Weapon Specialization"));
+        assert!(result.errors()[1]
+            .message
+            .contains("requires 7 Weapon Master"));
+        assert_eq!(result.warnings().len(), 1);
+        assert!(!result.is_valid());
+
+        // Weapon Specialization for a longsword requires Weapon Focus for it.
+        let character = Character::builder()
+            .class_levels(ClassLevels(vec![(Class::Fighter, 4)]))
+            .feats(vec![
+                get_weapon_feat("Weapon Focus", "Scimitar"),
+                get_weapon_feat("Weapon Specialization", "Longsword"),
+            ])
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .build();
+        let result = validate(&character);
+        assert_eq!(result.errors().len(), 1);
+
+        let character = Character::builder()
+            .size(SizeCategory::Small)
+            .feats(vec![get_feat("Dual Wielding"), get_feat("Zen Archery")])
+            .weapon(Weapon::new("".into(), get_weapon_base("Longsword"), vec![]))
+            .off_hand_weapon(Weapon::new(
+                "".into(),
+                get_weapon_base("Greatsword"),
+                vec![],
+            ))
+            .build();
+        let result = validate(&character);
+        // Two-handed main weapon, off-hand too large and missing WIS.
+        assert_eq!(result.errors().len(), 3, "{}", result);
+        assert!(result.to_string().contains("class prerequisites"));
+
+        let character = Character::builder()
+            .weapon(Weapon::new(
+                "".into(),
+                get_weapon_base("Greatsword"),
+                vec![],
+            ))
+            .build();
+        assert!(validate(&character).is_valid());
+
+        let character = Character {
+            size: SizeCategory::Small,
+            ..character
+        };
+        assert!(!validate(&character).is_valid());
+
+        let character = Character::builder()
+            .class_levels(ClassLevels(vec![(Class::Ranger, 10)]))
+            .favored_enemies(vec![RacialType::Orc, RacialType::Undead])
+            .build();
+        assert!(validate(&character).is_valid());
+
+        let character = Character::builder()
+            .class_levels(ClassLevels(vec![(Class::Ranger, 4), (Class::Fighter, 6)]))
+            .favored_enemies(vec![RacialType::Orc, RacialType::Undead])
+            .build();
+        let result = validate(&character);
+        assert!(result.errors()[0]
+            .message
+            .contains("allow 1 favored enemies, not 2"));
+
+        let character = Character::builder()
+            .class_levels(ClassLevels(vec![(Class::Ranger, 21)]))
+            .feats(vec![get_feat("Bane of Enemies")])
+            .build();
+        assert_eq!(validate(&character).warnings().len(), 1);
+    }
+}